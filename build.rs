@@ -0,0 +1,45 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+
+// Directories under src/ that make up the builtin assets.
+const ASSET_DIRECTORIES: &[&str] = &["shaders", "textures"];
+
+// With the embed-assets feature, lists every builtin asset for resource_pack.rs to include_bytes!,
+// so the embedded copy can't miss files added later.
+fn main() {
+    if env::var_os("CARGO_FEATURE_EMBED_ASSETS").is_none() {
+        return;
+    }
+
+    let source_directory = Path::new(&env::var("CARGO_MANIFEST_DIR").unwrap()).join("src");
+    let mut assets = vec![];
+    for directory in ASSET_DIRECTORIES {
+        println!("cargo:rerun-if-changed=src/{}", directory);
+        collect_assets(&source_directory, &source_directory.join(directory), &mut assets);
+    }
+    assets.sort();
+
+    let mut code = String::from("&[\n");
+    for asset in assets {
+        let path = source_directory.join(&asset);
+        code += &format!("    ({:?}, include_bytes!({:?}) as &[u8]),\n", asset, path.display().to_string());
+    }
+    code += "]\n";
+
+    let output = Path::new(&env::var("OUT_DIR").unwrap()).join("embedded_assets.rs");
+    fs::write(output, code).expect("Couldn't write the embedded asset list");
+}
+
+// Asset names are paths relative to src/ with forward slashes, like "textures/water.png".
+fn collect_assets(root: &Path, directory: &Path, assets: &mut Vec<String>) {
+    for entry in fs::read_dir(directory).expect("Couldn't read an asset directory") {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            collect_assets(root, &path, assets);
+        } else {
+            let relative = path.strip_prefix(root).unwrap();
+            assets.push(relative.components().map(|component| component.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/"));
+        }
+    }
+}
@@ -0,0 +1,65 @@
+pub type BlockId = u8;
+
+pub const AIR: BlockId = 0;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RenderType {
+    Opaque,
+    Cutout,     // alpha tested in the opaque pass: fully opaque or fully transparent texels (leaves, plants)
+    Translucent // alpha blended after every opaque block, sorted back to front (glass, water)
+}
+
+pub struct BlockType {
+    pub name: &'static str,
+    pub texture: &'static str,
+    pub render_type: RenderType,
+    pub solid: bool,
+    pub emission: u8 // block light level given off, 0-15
+}
+
+const fn block(name: &'static str, texture: &'static str, render_type: RenderType, solid: bool) -> BlockType {
+    BlockType{ name, texture, render_type, solid, emission: 0 }
+}
+
+const fn emissive(name: &'static str, texture: &'static str, emission: u8) -> BlockType {
+    BlockType{ name, texture, render_type: RenderType::Opaque, solid: true, emission }
+}
+
+// Indexed by BlockId - 1, the same index is the block's layer in the block texture array.
+pub const BLOCK_TYPES: [BlockType; 19] = [
+    block("gold_ore", "textures/gold_ore.png", RenderType::Opaque, true),
+    block("gold_block", "textures/gold_block.png", RenderType::Opaque, true),
+    block("dirt", "textures/dirt.png", RenderType::Opaque, true),
+    block("glass", "textures/glass.png", RenderType::Translucent, true),
+    block("netherrack", "textures/netherrack.png", RenderType::Opaque, true),
+    block("yellow_wool", "textures/yellow_wool.png", RenderType::Opaque, true),
+    block("granite", "textures/granite.png", RenderType::Opaque, true),
+    block("brown_wool", "textures/brown_wool.png", RenderType::Opaque, true),
+    block("blue_terracotta", "textures/blue_terracotta.png", RenderType::Opaque, true),
+    block("blue_wool", "textures/blue_wool.png", RenderType::Opaque, true),
+    block("jungle_planks", "textures/jungle_planks.png", RenderType::Opaque, true),
+    block("iron_ore", "textures/iron_ore.png", RenderType::Opaque, true),
+    block("red_sand", "textures/red_sand.png", RenderType::Opaque, true),
+    block("red_nether_bricks", "textures/red_nether_bricks.png", RenderType::Opaque, true),
+    emissive("redstone_block", "textures/redstone_block.png", 7),
+    block("warped_wart_block", "textures/warped_wart_block.png", RenderType::Opaque, true),
+    block("oak_leaves", "textures/oak_leaves.png", RenderType::Cutout, true),
+    block("water", "textures/water.png", RenderType::Translucent, false),
+    emissive("glowstone", "textures/glowstone.png", 15),
+];
+
+pub fn get_block_type(block: BlockId) -> Option<&'static BlockType> {
+    if block == AIR { None } else { BLOCK_TYPES.get(block as usize - 1) }
+}
+
+pub fn get_block_id(name: &str) -> Option<BlockId> {
+    BLOCK_TYPES.iter().position(|block_type| block_type.name == name).map(|index| index as BlockId + 1)
+}
+
+pub fn get_texture_layer(block: BlockId) -> u32 {
+    block as u32 - 1
+}
+
+pub fn get_texture_paths() -> Vec<&'static str> {
+    BLOCK_TYPES.iter().map(|block_type| block_type.texture).collect()
+}
@@ -0,0 +1,38 @@
+use crate::render_pass::{FrameContext, RenderPass, RenderStage, RenderState};
+use crate::shader_manager::{ProgramHandle, ShaderManager};
+use crate::shadow_map::SHADOW_MAP_TEXTURE_UNIT;
+
+pub const ALPHA_CUTOFF: f32 = 0.5; // cutout blocks drop texels below this alpha
+
+// Opaque and cutout blocks of every chunk.
+pub struct BlockPass {
+    program: ProgramHandle
+}
+
+impl BlockPass {
+    pub fn new(shader_manager: &mut ShaderManager) -> Self {
+        let program = shader_manager.load_or_fallback("shaders/main_vertex.glsl", "shaders/main_fragment.glsl");
+        BlockPass{ program }
+    }
+}
+
+impl RenderPass for BlockPass {
+    fn get_stage(&self) -> RenderStage {
+        RenderStage::Opaque
+    }
+
+    fn get_state(&self) -> RenderState {
+        RenderState::opaque()
+    }
+
+    fn draw(&mut self, context: &mut FrameContext) {
+        let program = context.shader_manager.get(self.program);
+        program.use_program();
+        program.set_texture("custom_texture", 0);
+        program.set_texture("u_shadow_map", SHADOW_MAP_TEXTURE_UNIT as i32);
+        program.set_float("u_alpha_cutoff", ALPHA_CUTOFF);
+
+        context.texture.activate_texture_array(gl::TEXTURE0, context.texture.get_id(context.chunk_renderer.get_block_textures()));
+        context.chunk_renderer.draw_opaque();
+    }
+}
@@ -20,6 +20,10 @@ impl BoundingBox {
         }
     }
 
+    pub fn get_min(&self) -> (f32, f32, f32) {
+        (self.min_x, self.min_y, self.min_z)
+    }
+
     pub fn intersects(&self, other: &BoundingBox) -> bool {
         self.min_x < other.max_x &&
             self.max_x > other.min_x &&
@@ -85,4 +89,59 @@ impl Player {
             self.velocity = (0.0, 0.0, 0.0);
         }
     }
+
+    // Standing on something: not inside any block, but would be after moving `distance` down.
+    pub fn is_supported(&self, blocks: &[BoundingBox], distance: f32) -> bool {
+        let mut lowered = self.bounding_box;
+        lowered.min_y -= distance;
+        lowered.max_y -= distance;
+        !blocks.iter().any(|block| self.bounding_box.intersects(block)) && blocks.iter().any(|block| lowered.intersects(block))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::get_block_id;
+    use crate::world::World;
+
+    fn floor() -> Vec<BoundingBox> {
+        let mut world = World::new();
+        world.set_block(0, 0, 0, get_block_id("dirt").unwrap());
+        world.get_bounding_boxes()
+    }
+
+    #[test]
+    fn player_stops_on_a_block() {
+        let mut player = Player::new(0.0, 1.0, 0.0, 0.5, 1.0, 0.5);
+        player.velocity = (0.0, -10.0, 0.0);
+        player.update(&floor(), 0.1);
+        assert!(player.bounding_box == BoundingBox::new(0.0, 1.0, 0.0, 0.5, 1.0, 0.5));
+        assert_eq!(player.velocity, (0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn player_moves_past_blocks_it_does_not_touch() {
+        let mut player = Player::new(2.0, 1.0, 0.0, 0.5, 1.0, 0.5);
+        player.velocity = (0.0, -10.0, 0.0);
+        player.update(&floor(), 0.1);
+        assert!(player.bounding_box == BoundingBox::new(2.0, 0.0, 0.0, 0.5, 1.0, 0.5));
+        assert_eq!(player.velocity, (0.0, -10.0, 0.0));
+    }
+
+    #[test]
+    fn player_is_supported_only_right_above_a_block() {
+        let blocks = floor();
+        assert!(Player::new(0.0, 0.6, 0.0, 0.5, 1.0, 0.5).is_supported(&blocks, 0.2));
+        assert!(!Player::new(0.0, 1.0, 0.0, 0.5, 1.0, 0.5).is_supported(&blocks, 0.2));
+        assert!(!Player::new(0.0, 0.2, 0.0, 0.5, 1.0, 0.5).is_supported(&blocks, 0.2));
+        assert!(!Player::new(2.0, 0.6, 0.0, 0.5, 1.0, 0.5).is_supported(&blocks, 0.2));
+    }
+
+    #[test]
+    fn touching_boxes_do_not_intersect() {
+        let block = BoundingBox::new(0.0, 0.0, 0.0, 0.5, 0.5, 0.5);
+        assert!(!block.intersects(&BoundingBox::new(0.5, 0.0, 0.0, 0.5, 0.5, 0.5)));
+        assert!(block.intersects(&BoundingBox::new(0.25, 0.25, 0.25, 0.5, 0.5, 0.5)));
+    }
 }
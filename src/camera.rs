@@ -11,7 +11,10 @@ pub struct Camera {
     sensitivity: f32,
     first_click: bool,
     matrix_projection: Mat4,
-    matrix_camera: Mat4
+    matrix_camera: Mat4,
+    fov_degree: f32,
+    near_plane: f32,
+    far_plane: f32
 }
 impl Camera {
     pub fn new(camera_position: Vec3, speed: f32, sensitivity: f32) -> Self {
@@ -25,6 +28,9 @@ impl Camera {
             first_click: true,
             matrix_projection: identity_matrix,
             matrix_camera: identity_matrix,
+            fov_degree: 90.0,
+            near_plane: 0.1,
+            far_plane: 100.0,
         }
     }
 
@@ -41,29 +47,56 @@ impl Camera {
     }
 
     pub fn set_projection(&mut self, fov_degree: f32, near_plane: f32, far_plane: f32) {
-        self.matrix_projection = perspective((WIDTH / HEIGHT) as f32, fov_degree.to_radians(), near_plane, far_plane);
+        self.fov_degree = fov_degree;
+        self.near_plane = near_plane;
+        self.far_plane = far_plane;
+        self.matrix_projection = perspective(self.get_aspect_ratio(), fov_degree.to_radians(), near_plane, far_plane);
     }
 
     pub fn set_camera_position(&mut self, camera_position: Vec3) {
         self.camera_position = camera_position;
     }
 
+    pub fn set_orientation(&mut self, orientation: Vec3) {
+        self.orientation = normalize(&orientation);
+    }
+
     pub fn update_camera_look_at(&mut self) {
         self.matrix_camera = look_at(&self.camera_position, &(self.camera_position + self.orientation), &self.camera_up);
     }
 
-    pub fn get_projection(&mut self) -> Mat4 {
+    pub fn get_projection(&self) -> Mat4 {
         self.matrix_projection
     }
 
-    pub fn get_camera_look_at(&mut self) -> Mat4 {
+    pub fn get_camera_look_at(&self) -> Mat4 {
         self.matrix_camera
     }
 
-    pub fn get_camera_position(&mut self) -> Vec3 {
+    pub fn get_camera_position(&self) -> Vec3 {
         self.camera_position
     }
 
+    pub fn get_orientation(&self) -> Vec3 {
+        self.orientation
+    }
+
+    pub fn get_fov_degree(&self) -> f32 {
+        self.fov_degree
+    }
+
+    pub fn get_near_plane(&self) -> f32 {
+        self.near_plane
+    }
+
+    pub fn get_far_plane(&self) -> f32 {
+        self.far_plane
+    }
+
+    pub fn get_aspect_ratio(&self) -> f32 {
+        (WIDTH / HEIGHT) as f32
+    }
+
     pub fn inputs(&mut self, win_sdl: &WinSDL, delta_time: f32) {
         let keyboard_state = win_sdl.event_pump.keyboard_state();
 
@@ -0,0 +1,274 @@
+use crate::block::{get_block_type, get_texture_layer, BlockId, RenderType};
+use crate::light::{get_emission, MAX_LIGHT};
+use crate::vertex_layout::VertexLayout;
+use crate::world::{ChunkPosition, World, CHUNK_SIZE};
+use nalgebra_glm::{vec3, Vec3};
+
+struct Face {
+    direction: [i32; 3],
+    corners: [[f32; 3]; 4] // counter-clockwise seen from outside, in the same order as ShapeData's cube
+}
+
+const FACES: [Face; 6] = [
+    Face{ direction: [-1, 0, 0], corners: [[-1.0, 1.0, -1.0], [-1.0, -1.0, -1.0], [-1.0, -1.0, 1.0], [-1.0, 1.0, 1.0]] }, // Left
+    Face{ direction: [0, 0, 1], corners: [[-1.0, 1.0, 1.0], [-1.0, -1.0, 1.0], [1.0, -1.0, 1.0], [1.0, 1.0, 1.0]] },     // Front
+    Face{ direction: [1, 0, 0], corners: [[1.0, 1.0, 1.0], [1.0, -1.0, 1.0], [1.0, -1.0, -1.0], [1.0, 1.0, -1.0]] },     // Right
+    Face{ direction: [0, 0, -1], corners: [[1.0, 1.0, -1.0], [1.0, -1.0, -1.0], [-1.0, -1.0, -1.0], [-1.0, 1.0, -1.0]] }, // Back
+    Face{ direction: [0, 1, 0], corners: [[-1.0, 1.0, -1.0], [-1.0, 1.0, 1.0], [1.0, 1.0, 1.0], [1.0, 1.0, -1.0]] },     // Top
+    Face{ direction: [0, -1, 0], corners: [[-1.0, -1.0, 1.0], [-1.0, -1.0, -1.0], [1.0, -1.0, -1.0], [1.0, -1.0, 1.0]] }, // Bottom
+];
+
+const TEXTURE_COORDINATES: [[f32; 2]; 4] = [[0.0, 1.0], [0.0, 0.0], [1.0, 0.0], [1.0, 1.0]];
+
+const BLOCK_HALF_SIZE: f32 = 0.5; // blocks are centered on their integer position
+
+const VERTEX_FLOATS: usize = 13;
+
+// CPU side mesh of one chunk. Opaque and cutout faces share a buffer, translucent faces get their own
+// so they can be drawn after everything else and re-sorted without remeshing.
+pub struct ChunkGeometry {
+    pub opaque_vertices: Vec<f32>,
+    pub opaque_indices: Vec<u32>,
+    pub translucent_vertices: Vec<f32>,
+    pub translucent_quads: Vec<TranslucentQuad>
+}
+
+pub struct TranslucentQuad {
+    pub center: Vec3,
+    pub flipped: bool
+}
+
+pub struct MeshOptions {
+    pub ambient_occlusion: bool
+}
+
+// position (location 0) + texture coordinate (location 1) + texture array layer (location 2) + normal (location 3)
+// + ambient occlusion, 0 fully occluded to 1 open (location 4) + block light, skylight and the block's own emission, 0 to 1 (location 5)
+pub fn get_chunk_layout() -> VertexLayout {
+    VertexLayout::new().float(0, 3).float(1, 2).float(2, 1).float(3, 3).float(4, 1).float(5, 3)
+}
+
+// Flipped quads are split along the 1-3 diagonal instead of 0-2.
+pub fn quad_indices(quad: u32, flipped: bool) -> [u32; 6] {
+    if flipped {
+        [4*quad + 1, 4*quad + 2, 4*quad + 3, 4*quad + 3, 4*quad, 4*quad + 1]
+    } else {
+        [4*quad, 4*quad + 1, 4*quad + 2, 4*quad + 2, 4*quad + 3, 4*quad]
+    }
+}
+
+pub fn mesh_chunk(world: &World, (chunk_x, chunk_y, chunk_z): ChunkPosition, options: &MeshOptions) -> ChunkGeometry {
+    let mut geometry = ChunkGeometry{ opaque_vertices: vec![], opaque_indices: vec![], translucent_vertices: vec![], translucent_quads: vec![] };
+
+    for y in chunk_y * CHUNK_SIZE..(chunk_y + 1) * CHUNK_SIZE {
+        for z in chunk_z * CHUNK_SIZE..(chunk_z + 1) * CHUNK_SIZE {
+            for x in chunk_x * CHUNK_SIZE..(chunk_x + 1) * CHUNK_SIZE {
+                let block = world.get_block(x, y, z);
+                let Some(block_type) = get_block_type(block) else { continue };
+
+                for face in &FACES {
+                    let neighbour = world.get_block(x + face.direction[0], y + face.direction[1], z + face.direction[2]);
+                    if is_face_hidden(block, neighbour) {
+                        continue;
+                    }
+
+                    let occlusion = if options.ambient_occlusion {
+                        face.corners.map(|corner| corner_occlusion(world, [x, y, z], face.direction, corner))
+                    } else {
+                        [3; 4]
+                    };
+                    // Split along the brighter diagonal, otherwise the occlusion of one corner
+                    // bleeds across the whole quad and the shading depends on the face's orientation.
+                    let flipped = occlusion[0] + occlusion[2] < occlusion[1] + occlusion[3];
+
+                    // A face is lit by the block in front of it.
+                    let (light_x, light_y, light_z) = (x + face.direction[0], y + face.direction[1], z + face.direction[2]);
+                    let light = [world.get_block_light(light_x, light_y, light_z), world.get_sky_light(light_x, light_y, light_z)];
+
+                    let center = vec3(x as f32, y as f32, z as f32);
+                    if block_type.render_type == RenderType::Translucent {
+                        push_face(&mut geometry.translucent_vertices, face, center, block, occlusion, light);
+                        let face_center = center + vec3(face.direction[0] as f32, face.direction[1] as f32, face.direction[2] as f32) * BLOCK_HALF_SIZE;
+                        geometry.translucent_quads.push(TranslucentQuad{ center: face_center, flipped });
+                    } else {
+                        let quad = (geometry.opaque_vertices.len() / (4 * VERTEX_FLOATS)) as u32;
+                        push_face(&mut geometry.opaque_vertices, face, center, block, occlusion, light);
+                        geometry.opaque_indices.extend_from_slice(&quad_indices(quad, flipped));
+                    }
+                }
+            }
+        }
+    }
+    geometry
+}
+
+// Opaque neighbours hide everything behind them. Two touching translucent blocks of the same type
+// (a body of water, a glass wall) have no face between them, different types keep both faces.
+fn is_face_hidden(block: BlockId, neighbour: BlockId) -> bool {
+    match get_block_type(neighbour).map(|neighbour_type| neighbour_type.render_type) {
+        Some(RenderType::Opaque) => true,
+        Some(RenderType::Translucent) => neighbour == block,
+        Some(RenderType::Cutout) | None => false
+    }
+}
+
+// Classic voxel AO: 0 (darkest) to 3 (open) from the two blocks along the edges next to the corner
+// and the one diagonally across it, all in the layer the face looks into.
+fn corner_occlusion(world: &World, block: [i32; 3], direction: [i32; 3], corner: [f32; 3]) -> u8 {
+    let mut sides = [[0; 3]; 2];
+    let mut side = 0;
+    for axis in 0..3 {
+        if direction[axis] == 0 {
+            sides[side][axis] = corner[axis] as i32;
+            side += 1;
+        }
+    }
+
+    let occludes = |offset: [i32; 3]| {
+        let neighbour = world.get_block(block[0] + direction[0] + offset[0], block[1] + direction[1] + offset[1], block[2] + direction[2] + offset[2]);
+        get_block_type(neighbour).is_some_and(|neighbour_type| neighbour_type.render_type == RenderType::Opaque)
+    };
+
+    let side1 = occludes(sides[0]);
+    let side2 = occludes(sides[1]);
+    let corner = occludes([sides[0][0] + sides[1][0], sides[0][1] + sides[1][1], sides[0][2] + sides[1][2]]);
+    ambient_occlusion(side1, side2, corner)
+}
+
+fn ambient_occlusion(side1: bool, side2: bool, corner: bool) -> u8 {
+    if side1 && side2 {
+        return 0; // the corner block can't be seen, the vertex is fully enclosed either way
+    }
+    3 - (side1 as u8 + side2 as u8 + corner as u8)
+}
+
+fn push_face(vertices: &mut Vec<f32>, face: &Face, center: Vec3, block: BlockId, occlusion: [u8; 4], light: [u8; 2]) {
+    let layer = get_texture_layer(block) as f32;
+    for ((corner, texture_coordinate), occlusion) in face.corners.iter().zip(TEXTURE_COORDINATES).zip(occlusion) {
+        vertices.extend_from_slice(&[
+            center.x + corner[0] * BLOCK_HALF_SIZE,
+            center.y + corner[1] * BLOCK_HALF_SIZE,
+            center.z + corner[2] * BLOCK_HALF_SIZE,
+            texture_coordinate[0],
+            texture_coordinate[1],
+            layer,
+            face.direction[0] as f32,
+            face.direction[1] as f32,
+            face.direction[2] as f32,
+            occlusion as f32 / 3.0,
+            light[0] as f32 / MAX_LIGHT as f32,
+            light[1] as f32 / MAX_LIGHT as f32,
+            get_emission(block) as f32 / MAX_LIGHT as f32
+        ]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::get_block_id;
+
+    fn world_with(blocks: &[([i32; 3], &str)]) -> World {
+        let mut world = World::new();
+        for ([x, y, z], name) in blocks {
+            world.set_block(*x, *y, *z, get_block_id(name).unwrap());
+        }
+        world
+    }
+
+    fn opaque_faces(geometry: &ChunkGeometry) -> usize {
+        geometry.opaque_vertices.len() / (4 * VERTEX_FLOATS)
+    }
+
+    fn mesh(world: &World) -> ChunkGeometry {
+        mesh_chunk(world, (0, 0, 0), &MeshOptions{ ambient_occlusion: true })
+    }
+
+    #[test]
+    fn lone_block_has_every_face() {
+        let geometry = mesh(&world_with(&[([1, 1, 1], "dirt")]));
+        assert_eq!(opaque_faces(&geometry), 6);
+        assert_eq!(geometry.opaque_indices.len(), 6 * 6);
+        assert!(geometry.translucent_quads.is_empty());
+    }
+
+    #[test]
+    fn touching_opaque_blocks_hide_the_faces_between_them() {
+        let geometry = mesh(&world_with(&[([1, 1, 1], "dirt"), ([2, 1, 1], "granite")]));
+        assert_eq!(opaque_faces(&geometry), 10);
+    }
+
+    #[test]
+    fn cutout_blocks_do_not_hide_faces() {
+        let geometry = mesh(&world_with(&[([1, 1, 1], "dirt"), ([2, 1, 1], "oak_leaves")]));
+        assert_eq!(opaque_faces(&geometry), 11); // the leaves' face towards the dirt is hidden
+    }
+
+    #[test]
+    fn translucent_faces_are_culled_only_between_the_same_type() {
+        let glass_wall = mesh(&world_with(&[([1, 1, 1], "glass"), ([2, 1, 1], "glass")]));
+        assert_eq!(glass_wall.translucent_quads.len(), 10);
+        assert_eq!(glass_wall.translucent_vertices.len(), 10 * 4 * VERTEX_FLOATS);
+
+        let glass_and_water = mesh(&world_with(&[([1, 1, 1], "glass"), ([2, 1, 1], "water")]));
+        assert_eq!(glass_and_water.translucent_quads.len(), 12);
+    }
+
+    #[test]
+    fn faces_towards_neighbouring_chunks_are_culled() {
+        let world = world_with(&[([CHUNK_SIZE - 1, 1, 1], "dirt"), ([CHUNK_SIZE, 1, 1], "dirt")]);
+        assert_eq!(opaque_faces(&mesh(&world)), 5);
+    }
+
+    #[test]
+    fn empty_chunk_has_no_geometry() {
+        let geometry = mesh(&world_with(&[([CHUNK_SIZE, 0, 0], "dirt")]));
+        assert!(geometry.opaque_vertices.is_empty() && geometry.opaque_indices.is_empty() && geometry.translucent_vertices.is_empty());
+    }
+
+    #[test]
+    fn ambient_occlusion_levels() {
+        assert_eq!(ambient_occlusion(false, false, false), 3);
+        assert_eq!(ambient_occlusion(true, false, false), 2);
+        assert_eq!(ambient_occlusion(false, true, false), 2);
+        assert_eq!(ambient_occlusion(false, false, true), 2);
+        assert_eq!(ambient_occlusion(true, false, true), 1);
+        assert_eq!(ambient_occlusion(true, true, false), 0);
+        assert_eq!(ambient_occlusion(true, true, true), 0);
+    }
+
+    // The top face of the block at (1, 1, 1), its corner at +x +z looks into (2, 2, 1), (1, 2, 2) and (2, 2, 2).
+    fn top_corner_occlusion(world: &World) -> u8 {
+        corner_occlusion(world, [1, 1, 1], [0, 1, 0], [1.0, 1.0, 1.0])
+    }
+
+    #[test]
+    fn corner_occlusion_samples_the_layer_in_front_of_the_face() {
+        assert_eq!(top_corner_occlusion(&world_with(&[([1, 1, 1], "dirt")])), 3);
+        assert_eq!(top_corner_occlusion(&world_with(&[([2, 2, 1], "dirt")])), 2);
+        assert_eq!(top_corner_occlusion(&world_with(&[([1, 2, 2], "dirt")])), 2);
+        assert_eq!(top_corner_occlusion(&world_with(&[([2, 2, 2], "dirt")])), 2);
+        assert_eq!(top_corner_occlusion(&world_with(&[([2, 2, 1], "dirt"), ([1, 2, 2], "dirt")])), 0);
+        assert_eq!(top_corner_occlusion(&world_with(&[([0, 2, 0], "dirt"), ([2, 1, 1], "dirt")])), 3); // other corner, same layer as the block
+    }
+
+    #[test]
+    fn only_opaque_blocks_occlude() {
+        assert_eq!(top_corner_occlusion(&world_with(&[([2, 2, 1], "glass"), ([1, 2, 2], "oak_leaves")])), 3);
+    }
+
+    #[test]
+    fn flipped_quads_split_along_the_other_diagonal() {
+        assert_eq!(quad_indices(0, false), [0, 1, 2, 2, 3, 0]);
+        assert_eq!(quad_indices(0, true), [1, 2, 3, 3, 0, 1]);
+        assert_eq!(quad_indices(2, true), [9, 10, 11, 11, 8, 9]);
+    }
+
+    #[test]
+    fn quads_split_along_the_brighter_diagonal() {
+        // Only the top face's +x +z corner (index 2) is darkened, so the split avoids it.
+        let geometry = mesh(&world_with(&[([1, 1, 1], "dirt"), ([2, 2, 2], "dirt")]));
+        let top = FACES.iter().position(|face| face.direction == [0, 1, 0]).unwrap() as u32;
+        assert_eq!(geometry.opaque_indices[top as usize * 6..][..6], quad_indices(top, true));
+    }
+}
@@ -0,0 +1,146 @@
+use crate::block::get_texture_paths;
+use crate::chunk_mesher::{get_chunk_layout, mesh_chunk, quad_indices, MeshOptions, TranslucentQuad};
+use crate::mesh::Mesh;
+use crate::texture::{Texture, TextureHandle};
+use crate::texture_animation::TextureAnimation;
+use crate::world::{ChunkPosition, World, CHUNK_SIZE};
+use nalgebra_glm::{distance2, vec3, Vec3};
+use std::collections::HashMap;
+
+struct ChunkMesh {
+    center: Vec3,
+    opaque: Option<Mesh>,
+    translucent: Option<Mesh>,
+    translucent_quads: Vec<TranslucentQuad>
+}
+
+// GPU meshes of every chunk in the world, shared by the opaque and the transparent pass.
+pub struct ChunkRenderer {
+    block_textures: TextureHandle,
+    animations: Vec<TextureAnimation>,
+    chunks: HashMap<ChunkPosition, ChunkMesh>,
+    sorted_from: Option<Vec3>,
+    options: MeshOptions
+}
+
+impl ChunkRenderer {
+    pub fn new(texture: &mut Texture) -> Self {
+        let (block_textures, animations) = texture.load_texture_array(&get_texture_paths());
+        ChunkRenderer{ block_textures, animations, chunks: HashMap::new(), sorted_from: None, options: MeshOptions{ ambient_occlusion: true } }
+    }
+
+    pub fn set_world(&mut self, world: &World) {
+        self.chunks.clear();
+        for chunk_position in world.get_chunk_positions() {
+            self.update_chunk(world, chunk_position);
+        }
+    }
+
+    // Changing mesh options remeshes every chunk.
+    pub fn set_ambient_occlusion(&mut self, world: &World, enabled: bool) {
+        if self.options.ambient_occlusion != enabled {
+            self.options.ambient_occlusion = enabled;
+            self.set_world(world);
+        }
+    }
+
+    // Remeshes one chunk, call it for every chunk whose blocks (or border neighbours) changed.
+    pub fn update_chunk(&mut self, world: &World, chunk_position: ChunkPosition) {
+        let geometry = mesh_chunk(world, chunk_position, &self.options);
+
+        let opaque = if geometry.opaque_indices.is_empty() {
+            None
+        } else {
+            Some(Mesh::new(&geometry.opaque_vertices, Some(&geometry.opaque_indices), get_chunk_layout()))
+        };
+
+        // Drawn in mesh order until the first sort.
+        let translucent = if geometry.translucent_quads.is_empty() {
+            None
+        } else {
+            let indices: Vec<u32> = geometry.translucent_quads.iter().enumerate().flat_map(|(quad, translucent_quad)| quad_indices(quad as u32, translucent_quad.flipped)).collect();
+            Some(Mesh::new(&geometry.translucent_vertices, Some(&indices), get_chunk_layout()))
+        };
+
+        self.chunks.insert(chunk_position, ChunkMesh{ center: chunk_center(chunk_position), opaque, translucent, translucent_quads: geometry.translucent_quads });
+        self.sorted_from = None;
+    }
+
+    pub fn reload_textures(&mut self, texture: &mut Texture) {
+        texture.release_texture(self.block_textures);
+        (self.block_textures, self.animations) = texture.load_texture_array(&get_texture_paths());
+    }
+
+    // Animated blocks only swap their texture layer, the meshes stay as they are.
+    pub fn update_animations(&mut self, texture: &Texture, delta_time: f32) {
+        let changed: Vec<bool> = self.animations.iter_mut().map(|animation| animation.update(delta_time)).collect();
+        if changed.contains(&true) {
+            let layers = self.animations.iter().zip(changed).filter(|(_, changed)| *changed).map(|(animation, _)| (animation.get_layer(), animation.get_frame()));
+            texture.update_texture_array(self.block_textures, layers);
+        }
+    }
+
+    pub fn get_block_textures(&self) -> TextureHandle {
+        self.block_textures
+    }
+
+    pub fn draw_opaque(&self) {
+        for chunk in self.chunks.values() {
+            if let Some(mesh) = &chunk.opaque {
+                mesh.draw(gl::TRIANGLES);
+            }
+        }
+    }
+
+    // Blending needs farther faces drawn first. Chunks are ordered on the fly, the faces inside
+    // each chunk are re-sorted into its index buffer only when the camera moved.
+    pub fn draw_translucent(&mut self, camera_position: Vec3) {
+        if self.sorted_from != Some(camera_position) {
+            for chunk in self.chunks.values_mut() {
+                sort_faces(chunk, camera_position);
+            }
+            self.sorted_from = Some(camera_position);
+        }
+
+        let mut chunks: Vec<&ChunkMesh> = self.chunks.values().filter(|chunk| chunk.translucent.is_some()).collect();
+        chunks.sort_by(|a, b| distance2(&b.center, &camera_position).total_cmp(&distance2(&a.center, &camera_position)));
+        for chunk in chunks {
+            if let Some(mesh) = &chunk.translucent {
+                mesh.draw(gl::TRIANGLES);
+            }
+        }
+    }
+}
+
+// Blocks are centred on their integer position, so a chunk spans from half a block before its first
+// block to half a block past its last one.
+fn chunk_center((x, y, z): ChunkPosition) -> Vec3 {
+    let half = CHUNK_SIZE as f32 / 2.0 - 0.5;
+    vec3(x as f32, y as f32, z as f32) * CHUNK_SIZE as f32 + vec3(half, half, half)
+}
+
+fn sort_faces(chunk: &mut ChunkMesh, camera_position: Vec3) {
+    let Some(mesh) = &mut chunk.translucent else { return };
+
+    let mut quads: Vec<u32> = (0..chunk.translucent_quads.len() as u32).collect();
+    let distance = |quad: &u32| distance2(&chunk.translucent_quads[*quad as usize].center, &camera_position);
+    quads.sort_by(|a, b| distance(b).total_cmp(&distance(a)));
+
+    let indices: Vec<u32> = quads.into_iter().flat_map(|quad| quad_indices(quad, chunk.translucent_quads[quad as usize].flipped)).collect();
+    mesh.update_indices(0, &indices);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunk_center_is_halfway_between_its_outer_blocks() {
+        assert_eq!(chunk_center((0, 0, 0)), vec3(7.5, 7.5, 7.5));
+        assert_eq!(chunk_center((1, -1, 2)), vec3(23.5, -8.5, 39.5));
+
+        let first_block = vec3(16.0, -16.0, 32.0);
+        let last_block = first_block + vec3(15.0, 15.0, 15.0);
+        assert_eq!(chunk_center((1, -1, 2)), (first_block + last_block) / 2.0);
+    }
+}
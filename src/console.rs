@@ -0,0 +1,54 @@
+use crate::world_clock::parse_time;
+use std::io::{stdin, BufRead};
+use std::path::PathBuf;
+use std::sync::mpsc::{channel, Receiver};
+use std::thread;
+
+// Commands typed into the terminal while the game runs:
+//   time set <hours|hh:mm|sunrise|noon|sunset|midnight>
+//   time
+//   packs set [path ...]   replaces the resource packs, the last one wins, no paths for only the builtin assets
+//   packs
+pub enum Command {
+    SetTime(f32),
+    QueryTime,
+    SetPacks(Vec<PathBuf>),
+    ListPacks
+}
+
+// Reads stdin on its own thread so the render loop never blocks on it.
+pub struct Console {
+    receiver: Receiver<String>
+}
+
+impl Console {
+    pub fn start() -> Self {
+        let (sender, receiver) = channel();
+        thread::spawn(move || {
+            for line in stdin().lock().lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        Console{ receiver }
+    }
+
+    // Commands entered since the last call. Invalid lines are reported and skipped.
+    pub fn poll(&self) -> Vec<Command> {
+        self.receiver.try_iter().filter_map(|line| {
+            parse_command(&line).map_err(|error| eprintln!("{}", error)).ok()
+        }).collect()
+    }
+}
+
+fn parse_command(line: &str) -> Result<Command, String> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    match words.as_slice() {
+        ["time"] => Ok(Command::QueryTime),
+        ["time", "set", value] => parse_time(value).map(Command::SetTime).ok_or(format!("Invalid time: {}", value)),
+        ["packs"] => Ok(Command::ListPacks),
+        ["packs", "set", paths @ ..] => Ok(Command::SetPacks(paths.iter().map(PathBuf::from).collect())),
+        _ => Err(format!("Unknown command: {}", line.trim()))
+    }
+}
@@ -0,0 +1,33 @@
+use crate::mesh::Mesh;
+use crate::render_pass::{FrameContext, RenderPass, RenderStage, RenderState};
+use crate::shader_manager::{ProgramHandle, ShaderManager};
+use crate::shape_data::ShapeData;
+
+pub struct CrosshairPass {
+    program: ProgramHandle,
+    mesh: Mesh
+}
+
+impl CrosshairPass {
+    pub fn new(shader_manager: &mut ShaderManager) -> Self {
+        let program = shader_manager.load_or_fallback("shaders/crosshair_vertex.glsl", "shaders/crosshair_fragment.glsl");
+        let mesh = Mesh::new(&ShapeData::get_crosshair_vertices(), None, ShapeData::get_crosshair_layout());
+
+        CrosshairPass{ program, mesh }
+    }
+}
+
+impl RenderPass for CrosshairPass {
+    fn get_stage(&self) -> RenderStage {
+        RenderStage::Overlay
+    }
+
+    fn get_state(&self) -> RenderState {
+        RenderState{ line_width: 3.0, ..RenderState::overlay() }
+    }
+
+    fn draw(&mut self, context: &mut FrameContext) {
+        context.shader_manager.get(self.program).use_program();
+        self.mesh.draw(gl::LINES); // vertical, then horizontal line
+    }
+}
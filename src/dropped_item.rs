@@ -0,0 +1,118 @@
+use crate::block::{get_block_type, get_texture_layer, BlockId};
+use crate::instanced_renderer::Instance;
+use crate::transform::Transform;
+use crate::world::World;
+use nalgebra_glm::{distance, vec3, Vec3};
+
+const ITEM_SIZE: f32 = 0.25; // blocks
+const FALL_SPEED: f32 = 4.0; // blocks per second
+const SPIN_SPEED: f32 = 90.0; // degrees per second
+const BOB_HEIGHT: f32 = 0.1;
+const PICKUP_DISTANCE: f32 = 1.5;
+const VOID_HEIGHT: f32 = -64.0; // items falling below this are gone
+
+// A broken block, lying around as a small spinning cube until the player walks up to it.
+pub struct DroppedItem {
+    block: BlockId,
+    position: Vec3, // bottom centre
+    age: f32
+}
+
+impl DroppedItem {
+    // Dropped from the bottom of the block it came from. Blocks are centred on integer coordinates.
+    pub fn new(block: BlockId, [x, y, z]: [i32; 3]) -> Self {
+        DroppedItem{ block, position: vec3(x as f32, y as f32 - 0.5, z as f32), age: 0.0 }
+    }
+
+    pub fn get_block(&self) -> BlockId {
+        self.block
+    }
+
+    // Falls until it rests on a solid block.
+    pub fn update(&mut self, world: &World, delta_time: f32) {
+        self.age += delta_time;
+
+        let next = self.position.y - FALL_SPEED * delta_time;
+        let below = (next + 0.5).floor() as i32;
+        let solid = get_block_type(world.get_block(self.position.x.round() as i32, below, self.position.z.round() as i32)).is_some_and(|block_type| block_type.solid);
+        self.position.y = if solid { next.max(below as f32 + 0.5) } else { next };
+    }
+
+    pub fn can_pick_up(&self, player_position: Vec3) -> bool {
+        distance(&self.position, &player_position) < PICKUP_DISTANCE
+    }
+
+    pub fn is_lost(&self) -> bool {
+        self.position.y < VOID_HEIGHT
+    }
+
+    // The unit cube from ShapeData spans -1 to 1, hence half the item size as scale.
+    pub fn get_instance(&self, transform: &mut Transform) -> Instance {
+        let bob = BOB_HEIGHT * (1.0 - (self.age * 2.0).cos()) / 2.0;
+        transform.set_position(self.position + vec3(0.0, ITEM_SIZE / 2.0 + bob, 0.0));
+        transform.set_scale(vec3(ITEM_SIZE / 2.0, ITEM_SIZE / 2.0, ITEM_SIZE / 2.0));
+        transform.set_euler_angles(vec3(0.0, self.age * SPIN_SPEED, 0.0));
+        transform.update();
+
+        Instance{ transform: transform.get_matrix(), layer: get_texture_layer(self.block) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::get_block_id;
+
+    fn settle(item: &mut DroppedItem, world: &World) {
+        for _ in 0..120 {
+            item.update(world, 1.0 / 60.0);
+        }
+    }
+
+    #[test]
+    fn falls_onto_the_block_below() {
+        let mut world = World::new();
+        world.set_block(0, 0, 0, get_block_id("dirt").unwrap());
+
+        let mut item = DroppedItem::new(get_block_id("glass").unwrap(), [0, 3, 0]);
+        settle(&mut item, &world);
+        assert_eq!(item.position, vec3(0.0, 0.5, 0.0));
+        assert!(!item.is_lost());
+    }
+
+    #[test]
+    fn falls_through_water() {
+        let mut world = World::new();
+        world.set_block(0, 0, 0, get_block_id("dirt").unwrap());
+        world.set_block(0, 1, 0, get_block_id("water").unwrap());
+
+        let mut item = DroppedItem::new(get_block_id("dirt").unwrap(), [0, 2, 0]);
+        settle(&mut item, &world);
+        assert_eq!(item.position.y, 0.5);
+    }
+
+    #[test]
+    fn stays_on_the_block_it_was_broken_from() {
+        let mut world = World::new();
+        world.set_block(0, 0, 0, get_block_id("dirt").unwrap());
+
+        let mut item = DroppedItem::new(get_block_id("dirt").unwrap(), [0, 1, 0]);
+        settle(&mut item, &world);
+        assert_eq!(item.position.y, 0.5);
+    }
+
+    #[test]
+    fn is_picked_up_within_reach() {
+        let item = DroppedItem::new(get_block_id("dirt").unwrap(), [0, 1, 0]);
+        assert!(item.can_pick_up(vec3(0.0, 1.5, 0.0)));
+        assert!(!item.can_pick_up(vec3(3.0, 1.5, 0.0)));
+    }
+
+    #[test]
+    fn instance_uses_the_block_texture_layer() {
+        let dirt = get_block_id("dirt").unwrap();
+        let instance = DroppedItem::new(dirt, [2, 1, 3]).get_instance(&mut Transform::new());
+        assert_eq!(instance.layer, get_texture_layer(dirt));
+        assert_eq!(instance.transform.column(3).xyz(), vec3(2.0, 0.5 + ITEM_SIZE / 2.0, 3.0));
+    }
+}
@@ -0,0 +1,76 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::io;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ShaderStage {
+    Vertex,
+    Fragment
+}
+
+impl ShaderStage {
+    pub fn gl_enum(&self) -> gl::types::GLenum {
+        match self {
+            ShaderStage::Vertex => gl::VERTEX_SHADER,
+            ShaderStage::Fragment => gl::FRAGMENT_SHADER
+        }
+    }
+}
+
+impl Display for ShaderStage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShaderStage::Vertex => write!(f, "vertex"),
+            ShaderStage::Fragment => write!(f, "fragment")
+        }
+    }
+}
+
+// Everything that can go wrong while loading shaders, programs, textures and their metadata.
+#[derive(Debug)]
+pub enum AssetError {
+    Io { path: String, source: io::Error },
+    Include { path: String, line: u32, message: String },
+    Compile { stage: ShaderStage, path: String, log: String },
+    Link { vertex_path: String, fragment_path: String, log: String },
+    MissingUniform { name: String },
+    UniformType { name: String, gl_type: gl::types::GLenum },
+    ImageDecode { path: String, source: image::ImageError },
+    Metadata { path: String, line: u32, message: String },
+    Pack { path: String, message: String }
+}
+
+impl AssetError {
+    pub fn from_image(path: &str, error: image::ImageError) -> Self {
+        match error {
+            image::ImageError::IoError(source) => AssetError::Io{ path: path.to_string(), source },
+            source => AssetError::ImageDecode{ path: path.to_string(), source }
+        }
+    }
+}
+
+impl Display for AssetError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AssetError::Io{ path, source } => write!(f, "{}: {}", path, source),
+            AssetError::Include{ path, line, message } => write!(f, "{}:{}: {}", path, line, message),
+            AssetError::Compile{ stage, path, log } => write!(f, "Failed to compile {} shader {}:\n{}", stage, path, log),
+            AssetError::Link{ vertex_path, fragment_path, log } => write!(f, "Failed to link {} + {}:\n{}", vertex_path, fragment_path, log),
+            AssetError::MissingUniform{ name } => write!(f, "Couldn't get uniform location for {}", name),
+            AssetError::UniformType{ name, gl_type } => write!(f, "Uniform {} has GL type 0x{:04X}, which doesn't match the value set", name, gl_type),
+            AssetError::ImageDecode{ path, source } => write!(f, "Failed to decode image {}: {}", path, source),
+            AssetError::Metadata{ path, line, message } => write!(f, "{}:{}: {}", path, line, message),
+            AssetError::Pack{ path, message } => write!(f, "Resource pack {}: {}", path, message)
+        }
+    }
+}
+
+impl Error for AssetError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            AssetError::Io{ source, .. } => Some(source),
+            AssetError::ImageDecode{ source, .. } => Some(source),
+            _ => None
+        }
+    }
+}
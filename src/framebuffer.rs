@@ -0,0 +1,89 @@
+use gl::types::{GLenum, GLint, GLsizei, GLuint};
+use std::ptr::null;
+
+// Offscreen render target: RGBA8 colour texture + depth/stencil renderbuffer.
+pub struct Framebuffer {
+    pub id: GLuint,
+    color_texture: GLuint,
+    depth_renderbuffer: GLuint,
+    width: u32,
+    height: u32
+}
+
+impl Framebuffer {
+    pub fn generate(width: u32, height: u32) -> Result<Self, &'static str> {
+        let mut id: GLuint = 0;
+        let mut color_texture: GLuint = 0;
+        let mut depth_renderbuffer: GLuint = 0;
+
+        unsafe {
+            gl::GenFramebuffers(1, &mut id);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, id);
+
+            gl::GenTextures(1, &mut color_texture);
+            gl::BindTexture(gl::TEXTURE_2D, color_texture);
+            gl::TexImage2D(gl::TEXTURE_2D, 0, gl::RGBA8 as GLint, width as GLsizei, height as GLsizei, 0, gl::RGBA, gl::UNSIGNED_BYTE, null());
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as GLint);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as GLint);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as GLint);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as GLint);
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+            gl::FramebufferTexture2D(gl::FRAMEBUFFER, gl::COLOR_ATTACHMENT0, gl::TEXTURE_2D, color_texture, 0);
+
+            gl::GenRenderbuffers(1, &mut depth_renderbuffer);
+            gl::BindRenderbuffer(gl::RENDERBUFFER, depth_renderbuffer);
+            gl::RenderbufferStorage(gl::RENDERBUFFER, gl::DEPTH24_STENCIL8, width as GLsizei, height as GLsizei);
+            gl::BindRenderbuffer(gl::RENDERBUFFER, 0);
+            gl::FramebufferRenderbuffer(gl::FRAMEBUFFER, gl::DEPTH_STENCIL_ATTACHMENT, gl::RENDERBUFFER, depth_renderbuffer);
+        }
+
+        let framebuffer = Framebuffer{ id, color_texture, depth_renderbuffer, width, height };
+
+        let status: GLenum = unsafe { gl::CheckFramebufferStatus(gl::FRAMEBUFFER) };
+        framebuffer.unbind();
+
+        if status != gl::FRAMEBUFFER_COMPLETE {
+            return Err("Framebuffer is not complete");
+        }
+
+        Ok(framebuffer)
+    }
+
+    pub fn bind(&self) {
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.id);
+            gl::Viewport(0, 0, self.width as GLsizei, self.height as GLsizei);
+        }
+    }
+
+    pub fn unbind(&self) {
+        unsafe { gl::BindFramebuffer(gl::FRAMEBUFFER, 0); }
+    }
+
+    pub fn get_color_texture(&self) -> GLuint {
+        self.color_texture
+    }
+
+    pub fn get_width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn get_height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn delete(&self) {
+        unsafe {
+            gl::DeleteRenderbuffers(1, &self.depth_renderbuffer);
+            gl::DeleteTextures(1, &self.color_texture);
+            gl::DeleteFramebuffers(1, &self.id);
+        }
+    }
+}
+
+impl Drop for Framebuffer {
+    fn drop(&mut self) {
+        self.unbind();
+        self.delete();
+    }
+}
@@ -0,0 +1,180 @@
+use crate::block::get_block_id;
+use crate::camera::Camera;
+use crate::dropped_item::DroppedItem;
+use crate::framebuffer::Framebuffer;
+use crate::renderer::Renderer;
+use crate::screenshot;
+use crate::transform::Transform;
+use crate::world::World;
+use crate::world_clock::{WorldClock, DEFAULT_DAY_LENGTH};
+use image::{Rgba, RgbaImage};
+use nalgebra_glm::{vec3, Vec3};
+use std::fs;
+use std::path::Path;
+
+// Same 2:1 aspect as the window so the projection matches the game.
+pub const GOLDEN_WIDTH: u32 = 320;
+pub const GOLDEN_HEIGHT: u32 = 160;
+
+const REFERENCE_DIRECTORY: &str = "./tests/golden";
+const OUTPUT_DIRECTORY: &str = "./target/golden";
+
+// A pixel counts as different when its YIQ distance is above this fraction of the maximum,
+// and a scene fails when more than MAX_DIFFERENT_PIXELS of its pixels differ.
+const PIXEL_THRESHOLD: f32 = 0.1;
+const MAX_DIFFERENT_PIXELS: f32 = 0.001;
+
+// Largest possible value of yiq_delta, reached between black and white.
+const MAX_YIQ_DELTA: f32 = 35215.0;
+
+// Everything a scene depends on is pinned here, so the images only change when the rendering does.
+pub struct GoldenScene {
+    pub name: &'static str,
+    pub camera_position: Vec3,
+    pub camera_orientation: Vec3,
+    pub seed: u32,
+    pub time: f32, // hours
+    pub items: &'static [(&'static str, [i32; 3])] // dropped block name and position, resting where it fell
+}
+
+pub fn scenes() -> Vec<GoldenScene> {
+    vec![
+        GoldenScene{ name: "blocks_overview", camera_position: vec3(8.0, 6.0, 20.0), camera_orientation: vec3(0.0, -0.5, -1.0), seed: 0, time: 12.0, items: &[] },
+        GoldenScene{ name: "blocks_close", camera_position: vec3(2.0, 1.5, 2.0), camera_orientation: vec3(1.0, -0.6, 1.0), seed: 0, time: 8.0, items: &[] },
+        GoldenScene{ name: "blocks_scattered", camera_position: vec3(8.0, 6.0, 20.0), camera_orientation: vec3(0.0, -0.5, -1.0), seed: 7, time: 15.0, items: &[] },
+        GoldenScene{ name: "dropped_items", camera_position: vec3(6.0, 1.0, 9.0), camera_orientation: vec3(0.0, -0.7, -1.0), seed: 0, time: 12.0, items: &[("dirt", [5, 1, 8]), ("glass", [6, 1, 7]), ("redstone_block", [7, 1, 8]), ("oak_leaves", [6, 1, 8])] },
+        GoldenScene{ name: "skybox_horizon", camera_position: vec3(8.0, 2.0, 8.0), camera_orientation: vec3(1.0, 0.1, 0.0), seed: 0, time: 18.0, items: &[] },
+        GoldenScene{ name: "skybox_up", camera_position: vec3(8.0, 2.0, 8.0), camera_orientation: vec3(0.0, 1.0, -0.2), seed: 0, time: 0.0, items: &[] },
+    ]
+}
+
+// Renders every scene in the world `create_world` builds from its seed and compares it with its
+// reference image, or rewrites the references when `update` is set. Returns false if any scene failed.
+pub fn run(renderer: &mut Renderer, create_world: fn(u32) -> World, update: bool) -> bool {
+    let target = match Framebuffer::generate(GOLDEN_WIDTH, GOLDEN_HEIGHT) {
+        Ok(target) => target,
+        Err(error) => {
+            eprintln!("GOLDEN: {}", error);
+            return false;
+        }
+    };
+
+    let mut passed = true;
+    for scene in scenes() {
+        renderer.set_world(&create_world(scene.seed));
+        renderer.set_lighting(WorldClock::new(scene.time, DEFAULT_DAY_LENGTH).get_lighting());
+        let mut transform = Transform::new();
+        let instances: Vec<_> = scene.items.iter().map(|&(block, position)| DroppedItem::new(get_block_id(block).unwrap(), position).get_instance(&mut transform)).collect();
+        renderer.set_instances(&instances);
+        let actual = render_scene(renderer, &target, &scene);
+        let reference_path = Path::new(REFERENCE_DIRECTORY).join(format!("{}.png", scene.name));
+
+        if update {
+            if let Err(error) = fs::create_dir_all(REFERENCE_DIRECTORY).and_then(|_| actual.save(&reference_path).map_err(std::io::Error::other)) {
+                eprintln!("GOLDEN {}: failed to write {}: {}", scene.name, reference_path.display(), error);
+                passed = false;
+            } else {
+                println!("GOLDEN {}: updated {}", scene.name, reference_path.display());
+            }
+            continue;
+        }
+
+        let reference = match image::open(&reference_path) {
+            Ok(reference) => reference.to_rgba8(),
+            Err(error) => {
+                eprintln!("GOLDEN {}: missing reference {} ({}), run with --golden-update", scene.name, reference_path.display(), error);
+                write_output(&scene, &actual, None);
+                passed = false;
+                continue;
+            }
+        };
+
+        if reference.dimensions() != actual.dimensions() {
+            eprintln!("GOLDEN {}: size {:?} does not match reference {:?}", scene.name, actual.dimensions(), reference.dimensions());
+            write_output(&scene, &actual, None);
+            passed = false;
+            continue;
+        }
+
+        let (different_pixels, diff) = compare(&reference, &actual);
+        let ratio = different_pixels as f32 / (actual.width() * actual.height()) as f32;
+
+        if ratio > MAX_DIFFERENT_PIXELS {
+            eprintln!("GOLDEN {}: FAILED, {} pixels differ ({:.3}%)", scene.name, different_pixels, ratio * 100.0);
+            write_output(&scene, &actual, Some(&diff));
+            passed = false;
+        } else {
+            println!("GOLDEN {}: ok ({} pixels differ)", scene.name, different_pixels);
+        }
+    }
+
+    passed
+}
+
+fn render_scene(renderer: &mut Renderer, target: &Framebuffer, scene: &GoldenScene) -> RgbaImage {
+    let mut camera = Camera::new(scene.camera_position, 0.0, 0.0);
+    camera.set_projection(120.0, 0.1, 100.0);
+    camera.set_orientation(scene.camera_orientation);
+    camera.update_camera_look_at();
+
+    target.bind();
+    renderer.render(&mut camera);
+    let image = screenshot::read_pixels(target.get_width(), target.get_height());
+    target.unbind();
+
+    image
+}
+
+// Counts perceptually different pixels and builds a diff image: differing pixels in red
+// over a faded greyscale copy of the reference.
+fn compare(reference: &RgbaImage, actual: &RgbaImage) -> (u32, RgbaImage) {
+    let mut diff = RgbaImage::new(reference.width(), reference.height());
+    let mut different_pixels = 0;
+
+    for (x, y, expected) in reference.enumerate_pixels() {
+        let delta = yiq_delta(expected, actual.get_pixel(x, y));
+
+        if delta > MAX_YIQ_DELTA * PIXEL_THRESHOLD * PIXEL_THRESHOLD {
+            different_pixels += 1;
+            diff.put_pixel(x, y, Rgba([255, 0, 0, 255]));
+        } else {
+            let luma = (0.1 * (0.299 * expected[0] as f32 + 0.587 * expected[1] as f32 + 0.114 * expected[2] as f32) + 229.5) as u8;
+            diff.put_pixel(x, y, Rgba([luma, luma, luma, 255]));
+        }
+    }
+
+    (different_pixels, diff)
+}
+
+// Squared colour distance in YIQ space, weighted towards luminance like the eye.
+fn yiq_delta(a: &Rgba<u8>, b: &Rgba<u8>) -> f32 {
+    let (r1, g1, b1) = (a[0] as f32, a[1] as f32, a[2] as f32);
+    let (r2, g2, b2) = (b[0] as f32, b[1] as f32, b[2] as f32);
+
+    let y = (r1 - r2) * 0.2988953 + (g1 - g2) * 0.5866225 + (b1 - b2) * 0.11448223;
+    let i = (r1 - r2) * 0.59597796 - (g1 - g2) * 0.2741761 - (b1 - b2) * 0.3218019;
+    let q = (r1 - r2) * 0.21147017 - (g1 - g2) * 0.5226171 + (b1 - b2) * 0.31114694;
+
+    0.5053 * y * y + 0.299 * i * i + 0.1957 * q * q
+}
+
+fn write_output(scene: &GoldenScene, actual: &RgbaImage, diff: Option<&RgbaImage>) {
+    if let Err(error) = fs::create_dir_all(OUTPUT_DIRECTORY) {
+        eprintln!("GOLDEN {}: failed to create {}: {}", scene.name, OUTPUT_DIRECTORY, error);
+        return;
+    }
+
+    let actual_path = Path::new(OUTPUT_DIRECTORY).join(format!("{}_actual.png", scene.name));
+    match actual.save(&actual_path) {
+        Ok(_) => eprintln!("GOLDEN {}: wrote {}", scene.name, actual_path.display()),
+        Err(error) => eprintln!("GOLDEN {}: failed to write {}: {}", scene.name, actual_path.display(), error)
+    }
+
+    if let Some(diff) = diff {
+        let diff_path = Path::new(OUTPUT_DIRECTORY).join(format!("{}_diff.png", scene.name));
+        match diff.save(&diff_path) {
+            Ok(_) => eprintln!("GOLDEN {}: wrote {}", scene.name, diff_path.display()),
+            Err(error) => eprintln!("GOLDEN {}: failed to write {}: {}", scene.name, diff_path.display(), error)
+        }
+    }
+}
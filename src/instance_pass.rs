@@ -0,0 +1,38 @@
+use crate::block_pass::ALPHA_CUTOFF;
+use crate::render_pass::{FrameContext, RenderPass, RenderStage, RenderState};
+use crate::shader_manager::{ProgramHandle, ShaderManager};
+use crate::shadow_map::SHADOW_MAP_TEXTURE_UNIT;
+
+// Every instanced cube (dropped items and the like), textured from the block texture array and
+// shaded by the same fragment shader as the blocks.
+pub struct InstancePass {
+    program: ProgramHandle
+}
+
+impl InstancePass {
+    pub fn new(shader_manager: &mut ShaderManager) -> Self {
+        let program = shader_manager.load_or_fallback("shaders/instance_vertex.glsl", "shaders/main_fragment.glsl");
+        InstancePass{ program }
+    }
+}
+
+impl RenderPass for InstancePass {
+    fn get_stage(&self) -> RenderStage {
+        RenderStage::Opaque
+    }
+
+    fn get_state(&self) -> RenderState {
+        RenderState::opaque()
+    }
+
+    fn draw(&mut self, context: &mut FrameContext) {
+        let program = context.shader_manager.get(self.program);
+        program.use_program();
+        program.set_texture("custom_texture", 0);
+        program.set_texture("u_shadow_map", SHADOW_MAP_TEXTURE_UNIT as i32);
+        program.set_float("u_alpha_cutoff", ALPHA_CUTOFF);
+
+        context.texture.activate_texture_array(gl::TEXTURE0, context.texture.get_id(context.chunk_renderer.get_block_textures()));
+        context.instanced_renderer.draw();
+    }
+}
@@ -0,0 +1,60 @@
+use crate::mesh::Mesh;
+use crate::object::VBO;
+use crate::vertex_layout::VertexLayout;
+use gl::types::GLsizei;
+use nalgebra_glm::Mat4;
+use std::ptr::null;
+
+// Per-instance attributes: transform at locations 2-5, texture array layer at 6.
+const INSTANCE_FLOATS: usize = 17;
+
+pub struct Instance {
+    pub transform: Mat4,
+    pub layer: u32
+}
+
+// Draws many copies of one indexed mesh with a single glDrawElementsInstanced call, for
+// everything that isn't part of a chunk mesh: dropped items, particles, debug cubes, entities.
+pub struct InstancedRenderer {
+    mesh: Mesh,
+    instance_vbo: VBO,
+    instance_count: usize
+}
+
+impl InstancedRenderer {
+    pub fn new(mesh: Mesh) -> Self {
+        let instance_vbo = VBO::generate();
+        instance_vbo.bind();
+        mesh.get_vao().set(&InstancedRenderer::get_instance_layout());
+        mesh.get_vao().unbind();
+
+        InstancedRenderer{ mesh, instance_vbo, instance_count: 0 }
+    }
+
+    pub fn get_instance_layout() -> VertexLayout {
+        VertexLayout::per_instance().mat4(2).float(6, 1)
+    }
+
+    // Replaces every instance. Cheap enough for data that changes each frame.
+    pub fn set_instances(&mut self, instances: &[Instance]) {
+        let mut data: Vec<f32> = Vec::with_capacity(instances.len() * INSTANCE_FLOATS);
+        for instance in instances {
+            data.extend_from_slice(instance.transform.as_slice());
+            data.push(instance.layer as f32);
+        }
+
+        self.instance_vbo.stream(&data);
+        self.instance_count = instances.len();
+    }
+
+    pub fn draw(&self) {
+        if self.instance_count == 0 {
+            return;
+        }
+
+        self.mesh.get_vao().bind();
+        unsafe {
+            gl::DrawElementsInstanced(gl::TRIANGLES, self.mesh.get_index_count() as GLsizei, gl::UNSIGNED_INT, null(), self.instance_count as GLsizei);
+        }
+    }
+}
@@ -0,0 +1,332 @@
+use crate::block::{get_block_type, BlockId, RenderType};
+use crate::world::{split, ChunkPosition, World, CHUNK_SIZE};
+use std::collections::{HashSet, VecDeque};
+
+pub const MAX_LIGHT: u8 = 15;
+
+type Position = [i32; 3];
+
+const NEIGHBOURS: [Position; 6] = [[1, 0, 0], [-1, 0, 0], [0, 1, 0], [0, -1, 0], [0, 0, 1], [0, 0, -1]];
+const DOWN: Position = [0, -1, 0];
+
+#[derive(Clone, Copy, PartialEq)]
+enum Channel {
+    Block,
+    Sky
+}
+
+const CHANNELS: [Channel; 2] = [Channel::Block, Channel::Sky];
+
+pub fn is_transparent_to_light(block: BlockId) -> bool {
+    get_block_type(block).is_none_or(|block_type| block_type.render_type != RenderType::Opaque)
+}
+
+pub fn get_emission(block: BlockId) -> u8 {
+    get_block_type(block).map_or(0, |block_type| block_type.emission)
+}
+
+// Lights every loaded chunk from scratch: skylight straight down every column until the first opaque
+// block, block light from every emitter, then both flood filled.
+pub fn light_world(world: &mut World) {
+    world.clear_light();
+
+    let chunk_positions = world.get_chunk_positions();
+    let Some(top) = chunk_positions.iter().map(|(_, y, _)| *y).max() else { return };
+    let bottom = chunk_positions.iter().map(|(_, y, _)| *y).min().unwrap();
+    let columns: HashSet<(i32, i32)> = chunk_positions.iter().map(|(x, _, z)| (*x, *z)).collect();
+
+    let mut sky_queue = VecDeque::new();
+    for (chunk_x, chunk_z) in columns {
+        for z in chunk_z * CHUNK_SIZE..(chunk_z + 1) * CHUNK_SIZE {
+            for x in chunk_x * CHUNK_SIZE..(chunk_x + 1) * CHUNK_SIZE {
+                let mut y = (top + 1) * CHUNK_SIZE - 1;
+                while y >= bottom * CHUNK_SIZE && is_transparent_to_light(world.get_block(x, y, z)) {
+                    set_light(world, Channel::Sky, [x, y, z], MAX_LIGHT);
+                    sky_queue.push_back([x, y, z]);
+                    y -= 1;
+                }
+            }
+        }
+    }
+
+    let mut block_queue = VecDeque::new();
+    for (chunk_x, chunk_y, chunk_z) in chunk_positions {
+        for y in chunk_y * CHUNK_SIZE..(chunk_y + 1) * CHUNK_SIZE {
+            for z in chunk_z * CHUNK_SIZE..(chunk_z + 1) * CHUNK_SIZE {
+                for x in chunk_x * CHUNK_SIZE..(chunk_x + 1) * CHUNK_SIZE {
+                    let emission = get_emission(world.get_block(x, y, z));
+                    if emission > 0 {
+                        set_light(world, Channel::Block, [x, y, z], emission);
+                        block_queue.push_back([x, y, z]);
+                    }
+                }
+            }
+        }
+    }
+
+    // Every chunk gets remeshed afterwards anyway, so nothing is tracked.
+    propagate(world, Channel::Sky, sky_queue, None);
+    propagate(world, Channel::Block, block_queue, None);
+}
+
+// Places or breaks a block and updates the light around it. Returns every loaded chunk that has to be
+// remeshed: the ones touching the block and the ones whose light changed.
+pub fn update_block(world: &mut World, [x, y, z]: Position, block: BlockId) -> HashSet<ChunkPosition> {
+    let new_chunk = !world.is_loaded(x, y, z);
+    world.set_block(x, y, z, block);
+
+    // A fresh chunk has no light at all yet, and may shade the ones below.
+    if new_chunk {
+        light_world(world);
+        return world.get_chunk_positions().into_iter().collect();
+    }
+
+    let mut changed = HashSet::new();
+    for offset_x in -1..=1 {
+        for offset_y in -1..=1 {
+            for offset_z in -1..=1 {
+                changed.insert(split(x + offset_x, y + offset_y, z + offset_z).0); // culling and AO of neighbours
+            }
+        }
+    }
+
+    for channel in CHANNELS {
+        let mut queue = remove_light(world, channel, [x, y, z], &mut changed);
+        if channel == Channel::Block && get_emission(block) > 0 {
+            mark_light(world, channel, [x, y, z], get_emission(block), &mut changed);
+            queue.push_back([x, y, z]);
+        }
+        propagate(world, channel, queue, Some(&mut changed));
+    }
+
+    changed.retain(|chunk_position| world.has_chunk(*chunk_position));
+    changed
+}
+
+// Darkens everything that got its light through `start` and returns the lit blocks bordering the
+// darkened area, which flood back into it from their own sources.
+fn remove_light(world: &mut World, channel: Channel, start: Position, changed: &mut HashSet<ChunkPosition>) -> VecDeque<Position> {
+    let mut refill = VecDeque::new();
+    let mut removal = VecDeque::from([(start, get_light(world, channel, start))]);
+    mark_light(world, channel, start, 0, changed);
+
+    while let Some((position, level)) = removal.pop_front() {
+        for offset in NEIGHBOURS {
+            let neighbour = add(position, offset);
+            if !world.is_loaded(neighbour[0], neighbour[1], neighbour[2]) {
+                continue;
+            }
+
+            let neighbour_level = get_light(world, channel, neighbour);
+            if neighbour_level == 0 {
+                continue;
+            }
+
+            let lit_by_position = neighbour_level < level || (channel == Channel::Sky && offset == DOWN && level == MAX_LIGHT);
+            if lit_by_position {
+                mark_light(world, channel, neighbour, 0, changed);
+                removal.push_back((neighbour, neighbour_level));
+
+                let emission = get_emission(world.get_block(neighbour[0], neighbour[1], neighbour[2]));
+                if channel == Channel::Block && emission > 0 {
+                    mark_light(world, channel, neighbour, emission, changed);
+                    refill.push_back(neighbour);
+                }
+            } else {
+                refill.push_back(neighbour);
+            }
+        }
+    }
+    refill
+}
+
+// BFS flood fill, one level darker per block. Full skylight travels straight down without fading.
+fn propagate(world: &mut World, channel: Channel, mut queue: VecDeque<Position>, mut changed: Option<&mut HashSet<ChunkPosition>>) {
+    while let Some(position) = queue.pop_front() {
+        let level = get_light(world, channel, position);
+        for offset in NEIGHBOURS {
+            let neighbour = add(position, offset);
+            if !world.is_loaded(neighbour[0], neighbour[1], neighbour[2]) || !is_transparent_to_light(world.get_block(neighbour[0], neighbour[1], neighbour[2])) {
+                continue;
+            }
+
+            let spread = if channel == Channel::Sky && offset == DOWN && level == MAX_LIGHT { MAX_LIGHT } else { level.saturating_sub(1) };
+            if get_light(world, channel, neighbour) < spread {
+                match changed.as_deref_mut() {
+                    Some(changed) => mark_light(world, channel, neighbour, spread, changed),
+                    None => set_light(world, channel, neighbour, spread)
+                }
+                queue.push_back(neighbour);
+            }
+        }
+    }
+}
+
+fn get_light(world: &World, channel: Channel, [x, y, z]: Position) -> u8 {
+    match channel {
+        Channel::Block => world.get_block_light(x, y, z),
+        Channel::Sky => world.get_sky_light(x, y, z)
+    }
+}
+
+fn set_light(world: &mut World, channel: Channel, [x, y, z]: Position, level: u8) {
+    match channel {
+        Channel::Block => world.set_block_light(x, y, z, level),
+        Channel::Sky => world.set_sky_light(x, y, z, level)
+    }
+}
+
+// Sets the light and marks the chunks to remesh. Faces of the neighbouring blocks sample this light,
+// so their chunks are marked as well.
+fn mark_light(world: &mut World, channel: Channel, [x, y, z]: Position, level: u8, changed: &mut HashSet<ChunkPosition>) {
+    set_light(world, channel, [x, y, z], level);
+
+    changed.insert(split(x, y, z).0);
+    for offset in NEIGHBOURS {
+        changed.insert(split(x + offset[0], y + offset[1], z + offset[2]).0);
+    }
+}
+
+fn add(position: Position, offset: Position) -> Position {
+    [position[0] + offset[0], position[1] + offset[1], position[2] + offset[2]]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::{get_block_id, AIR};
+
+    // Two chunks side by side along x with a dirt floor at y = 0, the border between x = 15 and 16.
+    fn two_chunk_world() -> World {
+        let mut world = World::new();
+        for x in 0..2 * CHUNK_SIZE {
+            for z in 0..CHUNK_SIZE {
+                world.set_block(x, 0, z, get_block_id("dirt").unwrap());
+            }
+        }
+        light_world(&mut world);
+        world
+    }
+
+    fn block_light(world: &World, [x, y, z]: Position) -> u8 {
+        world.get_block_light(x, y, z)
+    }
+
+    fn sky_light(world: &World, [x, y, z]: Position) -> u8 {
+        world.get_sky_light(x, y, z)
+    }
+
+    fn all_light(world: &World) -> Vec<(u8, u8)> {
+        let mut light = vec![];
+        for y in 0..CHUNK_SIZE {
+            for z in 0..CHUNK_SIZE {
+                for x in 0..2 * CHUNK_SIZE {
+                    light.push((world.get_block_light(x, y, z), world.get_sky_light(x, y, z)));
+                }
+            }
+        }
+        light
+    }
+
+    #[test]
+    fn block_light_fades_one_level_per_block() {
+        let mut world = two_chunk_world();
+        world.set_block(5, 1, 5, get_block_id("glowstone").unwrap());
+        light_world(&mut world);
+
+        assert_eq!(block_light(&world, [5, 1, 5]), 15);
+        assert_eq!(block_light(&world, [6, 1, 5]), 14);
+        assert_eq!(block_light(&world, [8, 1, 5]), 12);
+        assert_eq!(block_light(&world, [7, 2, 6]), 11);
+        assert_eq!(block_light(&world, [5, 0, 5]), 0, "opaque blocks stay dark");
+        assert_eq!(block_light(&world, [20, 1, 5]), 0);
+    }
+
+    #[test]
+    fn emitter_lights_across_a_chunk_border_and_breaking_it_darkens_both_sides() {
+        let mut world = two_chunk_world();
+        let changed = update_block(&mut world, [15, 1, 5], get_block_id("glowstone").unwrap());
+
+        assert_eq!(block_light(&world, [15, 1, 5]), 15);
+        assert_eq!(block_light(&world, [14, 1, 5]), 14);
+        assert_eq!(block_light(&world, [16, 1, 5]), 14);
+        assert_eq!(block_light(&world, [18, 1, 5]), 12);
+        assert!(changed.contains(&(0, 0, 0)) && changed.contains(&(1, 0, 0)));
+
+        let changed = update_block(&mut world, [15, 1, 5], AIR);
+        for x in 0..2 * CHUNK_SIZE {
+            assert_eq!(block_light(&world, [x, 1, 5]), 0, "x = {}", x);
+        }
+        assert!(changed.contains(&(0, 0, 0)) && changed.contains(&(1, 0, 0)));
+    }
+
+    #[test]
+    fn breaking_an_emitter_keeps_the_light_of_another() {
+        let mut world = two_chunk_world();
+        let glowstone = get_block_id("glowstone").unwrap();
+        update_block(&mut world, [14, 1, 5], glowstone);
+        update_block(&mut world, [20, 1, 5], glowstone);
+        assert_eq!(block_light(&world, [16, 1, 5]), 13);
+
+        update_block(&mut world, [14, 1, 5], AIR);
+        assert_eq!(block_light(&world, [14, 1, 5]), 9);
+        assert_eq!(block_light(&world, [16, 1, 5]), 11);
+        assert_eq!(block_light(&world, [20, 1, 5]), 15);
+    }
+
+    #[test]
+    fn roof_blocks_skylight_and_breaking_it_restores_it() {
+        let mut world = two_chunk_world();
+        assert_eq!(sky_light(&world, [5, 1, 5]), 15);
+
+        let dirt = get_block_id("dirt").unwrap();
+        for x in 4..=6 {
+            for z in 4..=6 {
+                update_block(&mut world, [x, 3, z], dirt);
+            }
+        }
+        assert_eq!(sky_light(&world, [5, 2, 5]), 13);
+        assert_eq!(sky_light(&world, [5, 1, 5]), 13);
+        assert_eq!(sky_light(&world, [4, 1, 4]), 14);
+        assert_eq!(sky_light(&world, [5, 4, 5]), 15);
+
+        update_block(&mut world, [5, 3, 5], AIR);
+        assert_eq!(sky_light(&world, [5, 2, 5]), 15);
+        assert_eq!(sky_light(&world, [5, 1, 5]), 15);
+    }
+
+    #[test]
+    fn translucent_blocks_let_skylight_through() {
+        let mut world = two_chunk_world();
+        update_block(&mut world, [5, 3, 5], get_block_id("glass").unwrap());
+        assert_eq!(sky_light(&world, [5, 2, 5]), 15);
+    }
+
+    #[test]
+    fn incremental_updates_match_a_full_relight() {
+        let mut world = two_chunk_world();
+        let [glowstone, dirt, redstone_block] = ["glowstone", "dirt", "redstone_block"].map(|name| get_block_id(name).unwrap());
+        let edits = [
+            ([15, 1, 5], glowstone), ([16, 2, 5], dirt), ([17, 1, 4], redstone_block), ([14, 3, 5], dirt),
+            ([15, 3, 5], dirt), ([16, 3, 5], dirt), ([15, 1, 5], AIR), ([16, 2, 5], AIR), ([15, 3, 5], AIR)
+        ];
+        for (position, block) in edits {
+            update_block(&mut world, position, block);
+            let incremental = all_light(&world);
+            light_world(&mut world);
+            assert!(incremental == all_light(&world), "after setting {:?} to block {}", position, block);
+        }
+    }
+
+    #[test]
+    fn block_in_a_new_chunk_relights_the_world() {
+        let mut world = two_chunk_world();
+        let changed = update_block(&mut world, [5, 20, 5], get_block_id("dirt").unwrap());
+
+        assert_eq!(changed, HashSet::from([(0, 0, 0), (1, 0, 0), (0, 1, 0)]));
+        assert_eq!(sky_light(&world, [5, 21, 5]), 15);
+        assert_eq!(sky_light(&world, [5, 19, 5]), 14);
+        assert_eq!(sky_light(&world, [5, 1, 5]), 14);
+        assert_eq!(sky_light(&world, [6, 1, 5]), 15);
+    }
+}
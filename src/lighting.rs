@@ -0,0 +1,69 @@
+use crate::object::{LIGHTING_BLOCK_BINDING, UBO};
+use nalgebra_glm::{normalize, vec3, vec4, Vec3, Vec4};
+
+// Sun, ambient light and sky colours shared by every lit program through the Lighting block in lighting.glsl.
+#[derive(Clone, Copy)]
+pub struct Lighting {
+    pub sun_direction: Vec3, // towards the light that casts shadows: the sun by day, the moon at night
+    pub sun_color: Vec3,
+    pub ambient_color: Vec3,
+    pub sky_zenith_color: Vec3,
+    pub sky_horizon_color: Vec3,
+    pub sun_position: Vec3, // towards the sun even below the horizon, the moon is opposite
+    pub daylight: f32,      // 0 at night to 1 by day
+    pub star_visibility: f32
+}
+
+impl Lighting {
+    // Fixed midday light, for scenes without a world clock.
+    pub fn new() -> Self {
+        let sun_direction = normalize(&vec3(0.4, 1.0, 0.3));
+        Lighting{
+            sun_direction,
+            sun_color: vec3(0.7, 0.7, 0.65),
+            ambient_color: vec3(0.35, 0.35, 0.4),
+            sky_zenith_color: vec3(0.25, 0.5, 0.9),
+            sky_horizon_color: vec3(0.7, 0.8, 0.95),
+            sun_position: sun_direction,
+            daylight: 1.0,
+            star_visibility: 0.0
+        }
+    }
+}
+
+pub struct LightingBuffer {
+    buffer: UBO
+}
+
+impl LightingBuffer {
+    pub fn new() -> Self {
+        LightingBuffer{ buffer: UBO::generate(6 * size_of::<Vec4>(), LIGHTING_BLOCK_BINDING) }
+    }
+
+    // Sky colours are picked as display colours, with linear rendering they're decoded like textures.
+    // Sun and ambient colours are light intensities and go up as they are.
+    pub fn upload(&self, lighting: &Lighting, srgb: bool) {
+        let decode = |color: Vec3| if srgb { color.map(srgb_to_linear) } else { color };
+        let sky_zenith_color = decode(lighting.sky_zenith_color);
+        let sky_horizon_color = decode(lighting.sky_horizon_color);
+
+        // std140 pads vec3 to 16 bytes, so everything goes up as vec4.
+        let direction = normalize(&lighting.sun_direction);
+        let sun_position = normalize(&lighting.sun_position);
+        self.buffer.set_vec4(0, &vec4(direction.x, direction.y, direction.z, 0.0));
+        self.buffer.set_vec4(size_of::<Vec4>(), &vec4(lighting.sun_color.x, lighting.sun_color.y, lighting.sun_color.z, 0.0));
+        self.buffer.set_vec4(2 * size_of::<Vec4>(), &vec4(lighting.ambient_color.x, lighting.ambient_color.y, lighting.ambient_color.z, 0.0));
+        self.buffer.set_vec4(3 * size_of::<Vec4>(), &vec4(sky_zenith_color.x, sky_zenith_color.y, sky_zenith_color.z, lighting.daylight));
+        self.buffer.set_vec4(4 * size_of::<Vec4>(), &vec4(sky_horizon_color.x, sky_horizon_color.y, sky_horizon_color.z, lighting.star_visibility));
+        self.buffer.set_vec4(5 * size_of::<Vec4>(), &vec4(sun_position.x, sun_position.y, sun_position.z, 0.0));
+    }
+}
+
+// The exact sRGB transfer function, same as the GPU uses for SRGB8_ALPHA8 textures.
+pub fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 { value / 12.92 } else { ((value + 0.055) / 1.055).powf(2.4) }
+}
+
+pub fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 { value * 12.92 } else { 1.055 * value.powf(1.0 / 2.4) - 0.055 }
+}
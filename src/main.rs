@@ -1,16 +1,30 @@
 use crate::camera::Camera;
-use crate::object::{create_program, IBO, VAO, VBO};
-use crate::shape_data::ShapeData;
-use crate::texture::Texture;
+use crate::console::{Command, Console};
+use crate::framebuffer::Framebuffer;
+use crate::golden::{GOLDEN_HEIGHT, GOLDEN_WIDTH};
+use crate::options::Options;
+use crate::post_process_pass::{Antialiasing, Tonemapping};
+use crate::renderer::Renderer;
+use crate::resource_pack::{AssetResolver, ResourcePack};
+use crate::texture::TextureFilter;
 use crate::transform::Transform;
 use crate::win_sdl::WinSDL;
-use gl::types::{GLboolean, GLchar, GLenum, GLint, GLsizei, GLuint};
+use gl::types::{GLchar, GLenum, GLsizei, GLuint};
+use image::RgbaImage;
 use nalgebra_glm::*;
 use sdl2::event::{Event, WindowEvent};
+use sdl2::keyboard::Scancode;
+use sdl2::mouse::MouseButton;
 use std::ffi::CStr;
 use std::os::raw::c_void;
+use std::process;
 use std::ptr::null;
-use crate::bounding_box::{BoundingBox, Player};
+use crate::block::{get_block_id, get_block_type, BlockId, AIR};
+use crate::bounding_box::Player;
+use crate::dropped_item::DroppedItem;
+use crate::light::{light_world, update_block};
+use crate::world::World;
+use crate::world_clock::WorldClock;
 //use std::env;
 
 mod win_sdl;
@@ -18,112 +32,121 @@ mod object;
 mod camera;
 mod transform;
 mod texture;
+mod texture_animation;
 mod shape_data;
 mod bounding_box;
+mod block;
+mod block_pass;
+mod chunk_mesher;
+mod chunk_renderer;
+mod console;
+mod crosshair_pass;
+mod dropped_item;
+mod error;
+mod framebuffer;
+mod golden;
+mod instance_pass;
+mod instanced_renderer;
+mod light;
+mod lighting;
+mod mesh;
+mod options;
+mod post_process_pass;
+mod render_pass;
+mod render_target;
+mod renderer;
+mod resource_pack;
+mod screenshot;
+mod shader_manager;
+mod shader_preprocessor;
+mod shadow_map;
+mod shadow_pass;
+mod skybox_pass;
+mod transparent_pass;
+mod vertex_layout;
+mod world;
+mod world_clock;
 
 const WIDTH: u32 = 1800;
 const HEIGHT: u32 = 900;
 
+const REACH: f32 = 8.0; // blocks
+const SCATTERED_BLOCKS: u32 = 12;
+
+const SIMULATION_STEP: f32 = 1.0 / 60.0; // seconds
+const SIMULATION_FALL_SPEED: f32 = 9.8; // blocks per second
+
 /*const GRID_SIZE: usize = 100;
 const SCALE: f64 = 0.05;     // Gürültü ölçeği (dağları daha küçük yapmak için)
 const HEIGHT_MULTIPLIER: f32 = 2.0;*/
 
 fn main() {
     //println!("Current working directory: {:?}", env::current_dir());
-    let mut win_sdl = WinSDL::new(WIDTH, HEIGHT).unwrap();
-    unsafe { gl::Viewport(0, 0, WIDTH as GLsizei, HEIGHT as GLsizei); }
-
-
-    let mut texture: Texture = Texture::new();
-    let image_array: Vec<GLuint> = vec![
-        texture.load_texture("./src/textures/gold_ore.png"),
-        texture.load_texture("./src/textures/gold_block.png"),
-        texture.load_texture("./src/textures/dirt.png"),
-        texture.load_texture("./src/textures/glass.png"),
-        texture.load_texture("./src/textures/netherrack.png"),
-        texture.load_texture("./src/textures/yellow_wool.png"),
-        texture.load_texture("./src/textures/granite.png"),
-        texture.load_texture("./src/textures/brown_wool.png"),
-        texture.load_texture("./src/textures/blue_terracotta.png"),
-        texture.load_texture("./src/textures/blue_wool.png"),
-        texture.load_texture("./src/textures/jungle_planks.png"),
-        texture.load_texture("./src/textures/iron_ore.png"),
-        texture.load_texture("./src/textures/red_sand.png"),
-        texture.load_texture("./src/textures/red_nether_bricks.png"),
-        texture.load_texture("./src/textures/redstone_block.png"),
-        texture.load_texture("./src/textures/warped_wart_block.png")
-
-    ];
-    let skybox_texture: GLuint = texture.load_cube_map_texture(vec!["./src/textures/right.jpg".to_string(), "./src/textures/left.jpg".to_string(), "./src/textures/top.jpg".to_string(), "./src/textures/bottom.jpg".to_string(), "./src/textures/front.jpg".to_string(), "./src/textures/back.jpg".to_string()]);
-
-
-
-    //--------------------------------------------------------------------------------              Skybox
-    let mut skybox_program = create_program("./src/shaders/skybox_vertex.glsl", "./src/shaders/skybox_fragment.glsl").unwrap();
-    skybox_program.use_program();
-
-    let vbo = VBO::generate();
-    vbo.set(&ShapeData::get_cube_vertices());
-
-    let vao = VAO::generate();
-    vao.set(false);
-
-    let ibo = IBO::generate();
-    ibo.set(&ShapeData::get_cube_indices());
-
-    skybox_program.add_uniform("u_matrix_projection");
-    skybox_program.add_uniform("u_matrix_camera");
-    skybox_program.add_uniform("u_matrix_transform");
-    //--------------------------------------------------------------------------------
-
-
-
-
-    //--------------------------------------------------------------------------------              Crosshair
-    let mut crosshair_program = create_program("./src/shaders/crosshair_vertex.glsl", "./src/shaders/crosshair_fragment.glsl").unwrap();
-    crosshair_program.use_program();
-
-    let crosshair_vbo = VBO::generate();
-    crosshair_vbo.set(&ShapeData::get_crosshair_vertices());
-
-    let crosshair_vao = VAO::generate();
-    crosshair_vao.set(true); // edit this func later
-    //--------------------------------------------------------------------------------
-
-
+    let options = Options::from_args().unwrap_or_else(|error| {
+        eprintln!("{}", error);
+        process::exit(1);
+    });
+
+    if options.simulate {
+        match run_simulation(options.seed, options.frames.unwrap_or(60)) {
+            Ok(player) => println!("Simulation: the player landed at y = {}", player.bounding_box.get_min().1),
+            Err(error) => {
+                eprintln!("Simulation failed: {}", error);
+                process::exit(1);
+            }
+        }
+        return;
+    }
 
+    let builtin_assets = ResourcePack::builtin(options.assets.as_deref()).unwrap_or_else(|error| {
+        eprintln!("{}", error);
+        process::exit(1);
+    });
 
-    //--------------------------------------------------------------------------------
-    let mut program = create_program("./src/shaders/main_vertex.glsl", "./src/shaders/main_fragment.glsl").unwrap();
-    program.use_program();
+    if options.golden || options.golden_update {
+        let _win_sdl = WinSDL::new_headless(GOLDEN_WIDTH, GOLDEN_HEIGHT).unwrap();
+        setup_gl_state();
 
-    //let (rand_vertices, rand_indices) = create_perlin_noise_grid_with_tex_coords(GRID_SIZE);
+        let mut renderer = Renderer::new(AssetResolver::new(builtin_assets, &[]).unwrap(), true);
+        let passed = golden::run(&mut renderer, create_world, options.golden_update);
+        process::exit(if passed { 0 } else { 1 });
+    }
 
-    let normal_vbo = VBO::generate();
-    normal_vbo.set(&ShapeData::get_cube_vertices());
-    //normal_vbo.set(&rand_vertices);
+    let mut win_sdl = if options.headless {
+        WinSDL::new_headless(WIDTH, HEIGHT).unwrap()
+    } else {
+        WinSDL::new(WIDTH, HEIGHT).unwrap()
+    };
+    unsafe { gl::Viewport(0, 0, WIDTH as GLsizei, HEIGHT as GLsizei); }
 
-    let normal_vao = VAO::generate();
-    normal_vao.set(false);
 
-    let normal_ibo = IBO::generate();
-    normal_ibo.set(&ShapeData::get_cube_indices());
-    //normal_ibo.set(&rand_indices);
+    let mut world = create_world(options.seed);
+    let glowstone = get_block_id("glowstone").unwrap();
+    let assets = AssetResolver::new(builtin_assets, &options.packs).unwrap_or_else(|error| {
+        eprintln!("{}", error);
+        process::exit(1);
+    });
+    let mut renderer = Renderer::new(assets, options.srgb);
+    renderer.set_world(&world);
+    renderer.set_fog_mode(options.fog_mode);
+    renderer.set_texture_filtering(options.texture_filtering);
 
-    program.add_uniform("u_matrix_projection");
-    program.add_uniform("u_matrix_camera");
-    program.add_uniform("u_matrix_transform");
-    program.add_uniform("custom_texture");
-    //--------------------------------------------------------------------------------
+    let mut clock = WorldClock::new(options.time, options.day_length);
+    let console = if options.headless { None } else { Some(Console::start()) };
+    let mut ambient_occlusion = options.ambient_occlusion;
+    renderer.set_ambient_occlusion(&world, ambient_occlusion);
 
 
 
 
     // BOUNDING BOX...
     //let mut player = Player::new(0.0, 0.0, 0.0, 0.5, 1.0, 0.5);
-    let mut blocks = vec![];
+    let mut blocks = world.get_bounding_boxes();
     /////////////////
 
+    let mut items: Vec<DroppedItem> = vec![];
+    let mut item_transform = Transform::new();
+
 
 
 
@@ -131,32 +154,55 @@ fn main() {
     let mut camera = Camera::new(vec3(0.0, 0.0, 0.0), 4.0, 0.7);
     camera.set_projection(120.0, 0.1, 100.0);
 
-    let mut transform = Transform::new();
-    transform.update();
-    let mut matrix_transform = transform.get_matrix();
 
 
 
+    // Headless runs have no usable default framebuffer, draw into our own.
+    let offscreen_target = if options.headless {
+        Some(Framebuffer::generate(WIDTH, HEIGHT).unwrap())
+    } else {
+        None
+    };
+    let mut frame_count: u32 = 0;
+    let mut screenshot_requested = false;
+    let mut high_res_screenshot_requested = false;
+
     let mut last_frame_time= win_sdl.sdl.timer().unwrap().ticks();
 
-    unsafe {
-        gl::Enable(gl::DEPTH_TEST);
-        //gl::Enable(gl::CULL_FACE);
-        //gl::Enable(gl::LINE_SMOOTH);
-        gl::Enable(gl::MULTISAMPLE);
-        gl::Enable(gl::DEBUG_OUTPUT);
-        gl::Enable(gl::DEBUG_OUTPUT_SYNCHRONOUS);
-        gl::DebugMessageCallback(Some(gl_debug_callback), null());
-    }
+    setup_gl_state();
 
     'running: loop {
 
+        if options.frames.is_some_and(|frames| frame_count >= frames) {
+            break 'running;
+        }
+        frame_count += 1;
+
         let current_frame_time = win_sdl.sdl.timer().unwrap().ticks();
 
         let delta_time = (current_frame_time - last_frame_time) as f32 / 1000.0;
 
         last_frame_time = current_frame_time;
 
+        for command in console.iter().flat_map(|console| console.poll()) {
+            match command {
+                Command::SetTime(time) => clock.set_time(time),
+                Command::QueryTime => println!("Time: {:02}:{:02}", clock.get_time() as u32, (clock.get_time().fract() * 60.0) as u32),
+                Command::SetPacks(paths) => match renderer.set_resource_packs(&paths) {
+                    Ok(()) => println!("Loaded {} resource pack(s)", paths.len()),
+                    Err(error) => eprintln!("{}", error)
+                },
+                Command::ListPacks => {
+                    for pack in renderer.get_resource_packs() {
+                        println!("{}  {}", pack.get_name(), pack.get_description());
+                    }
+                }
+            }
+        }
+        clock.update(delta_time);
+        renderer.update(delta_time);
+        renderer.set_lighting(clock.get_lighting());
+
 
 
         camera.inputs(&win_sdl, delta_time);
@@ -170,6 +216,71 @@ fn main() {
         for event in win_sdl.event_pump.poll_iter() {
             match event {
                 Event::Quit { .. } => break 'running,
+                Event::KeyDown { scancode: Some(Scancode::F2), repeat: false, .. } => screenshot_requested = true,
+                Event::KeyDown { scancode: Some(Scancode::F3), repeat: false, .. } => high_res_screenshot_requested = true,
+                Event::KeyDown { scancode: Some(Scancode::F4), repeat: false, .. } => {
+                    ambient_occlusion = !ambient_occlusion;
+                    renderer.set_ambient_occlusion(&world, ambient_occlusion);
+                },
+                Event::KeyDown { scancode: Some(Scancode::F6), repeat: false, .. } => {
+                    let mut post_process = renderer.get_post_process();
+                    post_process.antialiasing = match post_process.antialiasing {
+                        Antialiasing::None => Antialiasing::Msaa,
+                        Antialiasing::Msaa => Antialiasing::Fxaa,
+                        Antialiasing::Fxaa => Antialiasing::None
+                    };
+                    println!("Antialiasing: {:?}", post_process.antialiasing);
+                    renderer.set_post_process(post_process);
+                },
+                Event::KeyDown { scancode: Some(Scancode::F7), repeat: false, .. } => {
+                    let mut post_process = renderer.get_post_process();
+                    post_process.bloom = !post_process.bloom;
+                    renderer.set_post_process(post_process);
+                },
+                Event::KeyDown { scancode: Some(Scancode::F8), repeat: false, .. } => {
+                    let mut post_process = renderer.get_post_process();
+                    post_process.tonemapping = match post_process.tonemapping {
+                        Tonemapping::None => Tonemapping::Reinhard,
+                        Tonemapping::Reinhard => Tonemapping::Aces,
+                        Tonemapping::Aces => Tonemapping::None
+                    };
+                    println!("Tonemapping: {:?}", post_process.tonemapping);
+                    renderer.set_post_process(post_process);
+                },
+                Event::KeyDown { scancode: Some(Scancode::F9), repeat: false, .. } => {
+                    let mut filtering = renderer.get_texture_filtering();
+                    filtering.filter = match filtering.filter {
+                        TextureFilter::Nearest => TextureFilter::Pixelated,
+                        TextureFilter::Pixelated => TextureFilter::Trilinear,
+                        TextureFilter::Trilinear => TextureFilter::Nearest
+                    };
+                    println!("Texture filtering: {:?}", filtering.filter);
+                    renderer.set_texture_filtering(filtering);
+                },
+                Event::KeyDown { scancode: Some(Scancode::F10), repeat: false, .. } => {
+                    let mut post_process = renderer.get_post_process();
+                    post_process.srgb = !post_process.srgb;
+                    println!("sRGB: {}", if post_process.srgb { "on" } else { "off" });
+                    renderer.set_post_process(post_process);
+                },
+                // Break the block under the crosshair, or place glowstone against it.
+                Event::MouseButtonDown { mouse_btn, .. } => {
+                    if let Some((hit, previous)) = world.raycast(camera.get_camera_position(), camera.get_orientation(), REACH) {
+                        match mouse_btn {
+                            MouseButton::Left => {
+                                let [x, y, z] = hit;
+                                let block = world.get_block(x, y, z);
+                                if get_block_type(block).is_some_and(|block_type| block_type.solid) {
+                                    items.push(DroppedItem::new(block, hit));
+                                }
+                                edit_block(&mut world, &mut renderer, hit, AIR);
+                            },
+                            MouseButton::Right => edit_block(&mut world, &mut renderer, previous, glowstone),
+                            _ => continue
+                        }
+                        blocks = world.get_bounding_boxes();
+                    }
+                },
                 Event::Window { win_event, .. } => {
                     if let WindowEvent::Resized(width, height) = win_event {
                         unsafe { gl::Viewport(0, 0, width, height); }
@@ -179,111 +290,143 @@ fn main() {
             }
         }
 
-        unsafe {
-            gl::ClearColor(0.0, 0.0, 0.0, 1.0);
-            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
-
-
-
-
-            // SKYBOX
-            gl::Disable(gl::CULL_FACE);
-            vao.bind();
-            skybox_program.use_program();
-            skybox_program.set_mat4("u_matrix_projection", &camera.get_projection());
-            skybox_program.set_mat4("u_matrix_camera", &camera.get_camera_look_at());
-            skybox_program.set_mat4("u_matrix_transform", &matrix_transform);
-
-            transform.set_position(camera.get_camera_position());
-            transform.set_scale(vec3(2.0, 2.0, 2.0));
-            transform.set_euler_angles(vec3(0.0, 0.0, 0.0));
-            transform.update();
-            matrix_transform = transform.get_matrix();
-            skybox_program.set_mat4("u_matrix_transform", &matrix_transform);
-
-            gl::DepthMask(GLboolean::from(false));
-
-            texture.activate_cube_map_texture(skybox_texture);
-            gl::DrawElements(gl::TRIANGLES, ShapeData::get_cube_indices().len() as GLint, gl::UNSIGNED_INT, 0 as *const gl::types::GLvoid);
-            gl::DepthMask(GLboolean::from(true));
-            gl::Enable(gl::CULL_FACE);
-
-
-
-
-            // CROSSHAIR
-            crosshair_program.use_program();
-            crosshair_vao.bind();
-            gl::LineWidth(3.0);
-            gl::DrawArrays(gl::LINES, 0, 2);  // First two points (vertical)
-            gl::DrawArrays(gl::LINES, 2, 2);  // Last two points (horizontal)
-            gl::LineWidth(1.0);
-
-
-
-
-
-            // BLOCKS
-            program.use_program();
-            program.set_mat4("u_matrix_projection", &camera.get_projection());
-            program.set_mat4("u_matrix_camera", &camera.get_camera_look_at());
-            program.set_mat4("u_matrix_transform", &matrix_transform);
+        for item in items.iter_mut() {
+            item.update(&world, delta_time);
+        }
+        items.retain(|item| {
+            let picked_up = item.can_pick_up(camera.get_camera_position());
+            if picked_up {
+                println!("Picked up {}", get_block_type(item.get_block()).unwrap().name);
+            }
+            !picked_up && !item.is_lost()
+        });
+        let instances: Vec<_> = items.iter().map(|item| item.get_instance(&mut item_transform)).collect();
+        renderer.set_instances(&instances);
 
-            transform.set_position(vec3(0.0, 0.0, 0.0));
-            transform.set_scale(vec3(1.0, 1.0, 1.0));
-            transform.set_euler_angles(vec3(0.0, 0.0, 0.0));
-            transform.update();
-            matrix_transform = transform.get_matrix();
+        if let Some(target) = &offscreen_target {
+            target.bind();
+        }
 
-            program.set_mat4("u_matrix_transform", &matrix_transform);
-            program.set_texture("custom_texture", 0);
+        renderer.render(&mut camera);
 
-            normal_vao.bind();
-            /*texture.activate_texture(gl::TEXTURE0, image_array[0]);
-            gl::DrawElements(gl::TRIANGLES, rand_indices.len() as GLint, gl::UNSIGNED_INT, 0 as *const gl::types::GLvoid);*/
+        let last_frame = options.frames.is_some_and(|frames| frame_count >= frames);
+        if screenshot_requested || (options.screenshot && last_frame) {
+            screenshot_requested = false;
+            let (width, height) = match &offscreen_target {
+                Some(target) => (target.get_width(), target.get_height()),
+                None => win_sdl.window.drawable_size()
+            };
+            save_screenshot(&screenshot::read_pixels(width, height));
+        }
 
+        if high_res_screenshot_requested {
+            high_res_screenshot_requested = false;
+            let (width, height) = win_sdl.window.drawable_size();
+            match screenshot::capture_scaled(width, height, options.screenshot_scale, || renderer.render(&mut camera)) {
+                Ok(image) => save_screenshot(&image),
+                Err(error) => eprintln!("High-res screenshot failed: {}", error)
+            }
+            if let Some(target) = &offscreen_target {
+                target.bind();
+            }
+        }
 
-            for x in 0..16 {
-                for z in 0..16 {
-                    for y in 0..1 {
+        win_sdl.window.gl_swap_window();
+    }
+}
 
-                        let block = BoundingBox::new(x as f32, y as f32, z as f32, 0.5, 0.5, 0.5);
+fn setup_gl_state() {
+    unsafe {
+        gl::Enable(gl::DEPTH_TEST);
+        //gl::Enable(gl::CULL_FACE);
+        //gl::Enable(gl::LINE_SMOOTH);
+        gl::Enable(gl::MULTISAMPLE);
+        gl::Enable(gl::DEBUG_OUTPUT);
+        gl::Enable(gl::DEBUG_OUTPUT_SYNCHRONOUS);
+        gl::DebugMessageCallback(Some(gl_debug_callback), null());
+    }
+}
 
-                        if !blocks.contains(&block) {
-                            blocks.push(block);
-                        }
+fn save_screenshot(image: &RgbaImage) {
+    match screenshot::save(image, screenshot::SCREENSHOT_DIRECTORY) {
+        Ok(path) => println!("Saved screenshot to {}", path.display()),
+        Err(error) => eprintln!("{}", error)
+    }
+}
 
-                        transform.set_position(vec3(x as f32, y as f32, z as f32));
-                        transform.set_scale(vec3(0.5, 0.5, 0.5));
-                        transform.set_euler_angles(vec3(0.0, 0.0, 0.0));
-                        transform.update();
-                        matrix_transform = transform.get_matrix();
+// A 16x16 floor, one texture per row, with some cutout and translucent blocks on top. A non-zero
+// seed scatters a few more blocks over the free floor, the same ones every time.
+fn create_world(seed: u32) -> World {
+    let mut world = World::new();
+    for x in 0..16 {
+        for z in 0..16 {
+            world.set_block(x, 0, z, z as BlockId + 1);
+        }
+    }
 
-                        program.set_mat4("u_matrix_transform", &matrix_transform);
+    let oak_leaves = get_block_id("oak_leaves").unwrap();
+    let glass = get_block_id("glass").unwrap();
+    let water = get_block_id("water").unwrap();
+    for x in 2..5 {
+        for z in 2..4 {
+            world.set_block(x, 1, z, oak_leaves);
+        }
+    }
+    for x in 7..10 {
+        for y in 1..3 {
+            world.set_block(x, y, 6, glass);
+        }
+    }
+    for x in 11..14 {
+        for z in 10..13 {
+            world.set_block(x, 1, z, water);
+        }
+    }
+    world.set_block(6, 1, 12, get_block_id("glowstone").unwrap());
+
+    let mut random = seed;
+    for _ in 0..if seed == 0 { 0 } else { SCATTERED_BLOCKS } {
+        let [x, z, block] = [16, 16, 16].map(|range| {
+            random ^= random << 13; // xorshift32
+            random ^= random >> 17;
+            random ^= random << 5;
+            (random % range) as i32
+        });
+        if world.get_block(x, 1, z) == AIR {
+            world.set_block(x, 1, z, block as BlockId + 1);
+        }
+    }
 
-                        texture.activate_texture(gl::TEXTURE0, image_array[z]);
-                        gl::DrawElements(gl::TRIANGLES, ShapeData::get_cube_indices().len() as GLint, gl::UNSIGNED_INT, 0 as *const gl::types::GLvoid);
+    light_world(&mut world);
+    world
+}
 
-                    }
-                }
-            }
-            /*let player_bounding_box = player.bounding_box.clone();
-            if blocks.iter().any(|block| player_bounding_box.intersects(block)) {
-                // Çarpışma var, gerekli işlemleri yap
-                // Örneğin:
-                // - Oyuncuyu geri konumlandır
-                // - Hızını sıfırla
-                // - Çarpışma efekti oynat
-            } else {
-                // Çarpışma yok, normal harekete devam et
-            }*/
+// Places or breaks a block and remeshes every chunk whose blocks or light changed.
+fn edit_block(world: &mut World, renderer: &mut Renderer, position: [i32; 3], block: BlockId) {
+    for chunk_position in update_block(world, position, block) {
+        renderer.update_chunk(world, chunk_position);
+    }
+}
 
+// Physics only, no window and no GL context, so it runs on machines without a GPU. Drops a player
+// onto the terrain and checks that it lands on it instead of falling through or stopping mid-air.
+fn run_simulation(seed: u32, frames: u32) -> Result<Player, String> {
+    let blocks = create_world(seed).get_bounding_boxes();
 
+    let mut player = Player::new(8.0, 4.0, 8.0, 0.5, 1.0, 0.5);
+    player.velocity = (0.0, -SIMULATION_FALL_SPEED, 0.0);
 
+    for _ in 0..frames {
+        player.update(&blocks, SIMULATION_STEP);
+    }
 
-        }
-        win_sdl.window.gl_swap_window();
+    if player.velocity != (0.0, 0.0, 0.0) {
+        return Err(format!("the player is still falling after {} frames", frames));
     }
+    if !player.is_supported(&blocks, SIMULATION_FALL_SPEED * SIMULATION_STEP) {
+        return Err("the player stopped without standing on a block".to_string());
+    }
+    Ok(player)
 }
 
 extern "system" fn gl_debug_callback(
@@ -347,4 +490,29 @@ extern "system" fn gl_debug_callback(
     }
 
     (vertices, indices)
-}*/
\ No newline at end of file
+}*/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn simulated_player_lands_on_the_floor() {
+        let player = run_simulation(0, 60).unwrap();
+        let (_, y, _) = player.bounding_box.get_min();
+        assert!((0.5..=0.5 + SIMULATION_FALL_SPEED * SIMULATION_STEP).contains(&y), "landed at y = {}", y);
+    }
+
+    #[test]
+    fn simulated_player_lands_in_scattered_worlds() {
+        for seed in 1..20 {
+            if let Err(error) = run_simulation(seed, 60) {
+                panic!("seed {}: {}", seed, error);
+            }
+        }
+    }
+
+    #[test]
+    fn short_simulation_reports_a_falling_player() {
+        assert!(run_simulation(0, 5).is_err());
+    }
+}
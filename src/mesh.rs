@@ -0,0 +1,81 @@
+use crate::object::{IBO, VAO, VBO};
+use crate::vertex_layout::VertexLayout;
+use gl::types::{GLenum, GLsizei};
+use std::ptr::null;
+
+// A VAO together with the buffers it reads from. `vao` is declared first so it is
+// dropped (and unbound) before the IBO unbinds itself.
+pub struct Mesh {
+    vao: VAO,
+    vbo: VBO,
+    ibo: Option<IBO>,
+    layout: VertexLayout,
+    vertex_count: usize,
+    index_count: usize
+}
+
+impl Mesh {
+    pub fn new(vertices: &[f32], indices: Option<&[u32]>, layout: VertexLayout) -> Self {
+        assert!(layout.stride > 0, "Mesh needs at least one vertex attribute");
+
+        let vao = VAO::generate();
+        vao.bind();
+
+        let vbo = VBO::generate();
+        vbo.set(vertices);
+        vao.set(&layout);
+
+        let ibo = indices.map(|indices| {
+            let ibo = IBO::generate();
+            ibo.set(indices);
+            ibo
+        });
+
+        vao.unbind();
+
+        let vertex_count = vertex_count(vertices, &layout);
+        Mesh{ vao, vbo, ibo, layout, vertex_count, index_count: indices.map_or(0, |indices| indices.len()) }
+    }
+
+    // Overwrites part of the vertex data in place, offset counted in floats.
+    pub fn update_vertices(&mut self, offset: usize, vertices: &[f32]) {
+        self.vbo.sub_data(offset, vertices);
+    }
+
+    // Overwrites part of the index data in place, offset counted in indices.
+    pub fn update_indices(&mut self, offset: usize, indices: &[u32]) {
+        if let Some(ibo) = &self.ibo {
+            self.vao.bind();
+            ibo.sub_data(offset, indices);
+            self.vao.unbind();
+        }
+    }
+
+    // Replaces all vertex data, for meshes rebuilt every frame. The vertex count may change.
+    pub fn stream_vertices(&mut self, vertices: &[f32]) {
+        self.vbo.stream(vertices);
+        self.vertex_count = vertex_count(vertices, &self.layout);
+    }
+
+    pub fn get_vao(&self) -> &VAO {
+        &self.vao
+    }
+
+    pub fn get_index_count(&self) -> usize {
+        self.index_count
+    }
+
+    pub fn draw(&self, primitive: GLenum) {
+        self.vao.bind();
+        unsafe {
+            match self.ibo {
+                Some(_) => gl::DrawElements(primitive, self.index_count as GLsizei, gl::UNSIGNED_INT, null()),
+                None => gl::DrawArrays(primitive, 0, self.vertex_count as GLsizei)
+            }
+        }
+    }
+}
+
+fn vertex_count(vertices: &[f32], layout: &VertexLayout) -> usize {
+    size_of_val(vertices) / layout.stride
+}
@@ -1,13 +1,36 @@
-use gl::types::{GLchar, GLenum, GLint, GLsizeiptr, GLuint};
-use nalgebra_glm::{Mat3, Mat4, Vec3, Vec4};
-use std::collections::HashMap;
+use gl::types::{GLboolean, GLchar, GLenum, GLint, GLsizei, GLsizeiptr, GLuint};
+use nalgebra_glm::{Mat3, Mat4, Vec2, Vec3, Vec4};
+use std::collections::{HashMap, HashSet};
 use std::ffi::{CStr, CString};
-use std::fs::read_to_string;
 use std::ptr::{null, null_mut};
+use crate::error::{AssetError, ShaderStage};
+use crate::resource_pack::AssetResolver;
+use crate::shader_preprocessor::{preprocess, PreprocessedSource};
+use crate::vertex_layout::VertexLayout;
+
+// Binding points shared by every program, blocks are bound to them by name after linking.
+pub const CAMERA_BLOCK_BINDING: GLuint = 0;
+pub const LIGHTING_BLOCK_BINDING: GLuint = 1;
+pub const SHADOWS_BLOCK_BINDING: GLuint = 2;
+const UNIFORM_BLOCK_BINDINGS: &[(&str, GLuint)] = &[("Camera", CAMERA_BLOCK_BINDING), ("Lighting", LIGHTING_BLOCK_BINDING), ("Shadows", SHADOWS_BLOCK_BINDING)];
+
+const SAMPLER_TYPES: &[GLenum] = &[
+    gl::SAMPLER_2D, gl::SAMPLER_3D, gl::SAMPLER_CUBE, gl::SAMPLER_2D_ARRAY, gl::SAMPLER_2D_SHADOW,
+    gl::SAMPLER_2D_ARRAY_SHADOW, gl::SAMPLER_2D_MULTISAMPLE
+];
+
+#[derive(Clone, Copy)]
+struct Uniform {
+    location: GLint,
+    gl_type: GLenum
+}
 
 pub struct Program {
     id: GLuint,
-    uniform_list: HashMap<&'static str, GLint>
+    uniform_list: HashMap<String, Uniform>, // name: reflected uniform, filled after linking
+    attribute_list: HashMap<String, GLint>, // name: location
+    reported_uniforms: HashSet<String>,
+    source_files: Vec<String>
 }
 
 impl Program {
@@ -34,48 +57,137 @@ impl Program {
             return Err(error.to_string_lossy().into_owned());
         }
 
-        Ok(Program{ id, uniform_list: HashMap::new() })
+        let source_files = shaders.iter().flat_map(|shader| shader.source_files.iter().cloned()).collect();
+
+        let mut program = Program{ id, uniform_list: HashMap::new(), attribute_list: HashMap::new(), reported_uniforms: HashSet::new(), source_files };
+        program.reflect();
+        Ok(program)
+    }
+
+    // Caches every active uniform and attribute and binds the known uniform blocks.
+    fn reflect(&mut self) {
+        let mut max_length: GLint = 0;
+        unsafe { gl::GetProgramiv(self.id, gl::ACTIVE_UNIFORM_MAX_LENGTH, &mut max_length); }
+
+        for index in 0..self.get_parameter(gl::ACTIVE_UNIFORMS) {
+            let mut size: GLint = 0;
+            let mut gl_type: GLenum = 0;
+            let name = read_resource_name(max_length, |length, name| unsafe {
+                gl::GetActiveUniform(self.id, index as GLuint, max_length, length, &mut size, &mut gl_type, name);
+            });
+
+            let cname = CString::new(name.as_str()).expect("CString::new error in Uniform.");
+            let location = unsafe { gl::GetUniformLocation(self.id, cname.as_ptr()) };
+            if location == -1 {
+                continue; // member of a uniform block
+            }
+
+            let name = name.strip_suffix("[0]").map(str::to_string).unwrap_or(name);
+            self.uniform_list.insert(name, Uniform{ location, gl_type });
+        }
+
+        unsafe { gl::GetProgramiv(self.id, gl::ACTIVE_ATTRIBUTE_MAX_LENGTH, &mut max_length); }
+
+        for index in 0..self.get_parameter(gl::ACTIVE_ATTRIBUTES) {
+            let mut size: GLint = 0;
+            let mut gl_type: GLenum = 0;
+            let name = read_resource_name(max_length, |length, name| unsafe {
+                gl::GetActiveAttrib(self.id, index as GLuint, max_length, length, &mut size, &mut gl_type, name);
+            });
+
+            let cname = CString::new(name.as_str()).expect("CString::new error in Attribute.");
+            let location = unsafe { gl::GetAttribLocation(self.id, cname.as_ptr()) };
+            if location != -1 { // built-ins like gl_VertexID have no location
+                self.attribute_list.insert(name, location);
+            }
+        }
+
+        for index in 0..self.get_parameter(gl::ACTIVE_UNIFORM_BLOCKS) {
+            let mut length: GLint = 0;
+            unsafe { gl::GetActiveUniformBlockiv(self.id, index as GLuint, gl::UNIFORM_BLOCK_NAME_LENGTH, &mut length); }
+            let name = read_resource_name(length, |written, name| unsafe {
+                gl::GetActiveUniformBlockName(self.id, index as GLuint, length, written, name);
+            });
+
+            match UNIFORM_BLOCK_BINDINGS.iter().find(|(block, _)| *block == name) {
+                Some((_, binding)) => unsafe { gl::UniformBlockBinding(self.id, index as GLuint, *binding); },
+                None => eprintln!("Uniform block {} has no binding point", name)
+            }
+        }
+    }
+
+    fn get_parameter(&self, parameter: GLenum) -> GLint {
+        let mut value: GLint = 0;
+        unsafe { gl::GetProgramiv(self.id, parameter, &mut value); }
+        value
     }
 
     pub fn use_program(&self) {
         unsafe { gl::UseProgram(self.id); }
     }
 
-    pub fn add_uniform(&mut self, name: &'static str) {
-        let cname: CString = CString::new(name).expect("CString::new error in Uniform.");
-        let location: GLint = unsafe { gl::GetUniformLocation(self.id, cname.as_ptr()) };
+    pub fn get_attribute_location(&self, name: &str) -> Option<GLint> {
+        self.attribute_list.get(name).copied()
+    }
+
+    // Every file the shaders were built from, including #include'd ones.
+    pub fn source_files(&self) -> &[String] {
+        &self.source_files
+    }
 
-        if location == -1 {
-            eprintln!("Couldn't get uniform location for {}", name);
-            return;
+    // Location of `name` if it exists with one of the accepted types. Problems are
+    // reported once per uniform instead of every frame.
+    fn location(&mut self, name: &str, accepted_types: &[GLenum]) -> Option<GLint> {
+        let error = match self.uniform_list.get(name) {
+            Some(uniform) if accepted_types.contains(&uniform.gl_type) => return Some(uniform.location),
+            Some(uniform) => AssetError::UniformType{ name: name.to_string(), gl_type: uniform.gl_type },
+            None => AssetError::MissingUniform{ name: name.to_string() }
+        };
+
+        if self.reported_uniforms.insert(name.to_string()) {
+            eprintln!("{}", error);
         }
+        None
+    }
+
+    pub fn set_float(&mut self, name: &str, value: f32) {
+        let Some(location) = self.location(name, &[gl::FLOAT]) else { return };
+        unsafe { gl::Uniform1f(location, value); }
+    }
+
+    pub fn set_vec2(&mut self, name: &str, vec2: &Vec2) {
+        let Some(location) = self.location(name, &[gl::FLOAT_VEC2]) else { return };
+        unsafe { gl::Uniform2f(location, vec2[0], vec2[1]); }
+    }
 
-        self.uniform_list.insert(name, location);
+    pub fn set_int(&mut self, name: &str, value: GLint) {
+        let Some(location) = self.location(name, &[gl::INT]) else { return };
+        unsafe { gl::Uniform1i(location, value); }
     }
 
     pub fn set_vec3(&mut self, name: &str, vec3: &Vec3) {
-        let location = self.uniform_list.get(name).expect(format!("Uniform List, Getting location error: {}.", name).as_str());
-        unsafe { gl::Uniform3f(*location, vec3[0], vec3[1], vec3[2]); }
+        let Some(location) = self.location(name, &[gl::FLOAT_VEC3]) else { return };
+        unsafe { gl::Uniform3f(location, vec3[0], vec3[1], vec3[2]); }
     }
 
     pub fn set_vec4(&mut self, name: &str, vec4: &Vec4) {
-        let location = self.uniform_list.get(name).expect(format!("Uniform List, Getting location error: {}.", name).as_str());
-        unsafe { gl::Uniform4f(*location, vec4[0], vec4[1], vec4[2], vec4[3]); }
+        let Some(location) = self.location(name, &[gl::FLOAT_VEC4]) else { return };
+        unsafe { gl::Uniform4f(location, vec4[0], vec4[1], vec4[2], vec4[3]); }
     }
 
     pub fn set_mat3(&mut self, name: &str, mat3: &Mat3) {
-        let location = self.uniform_list.get(name).expect(format!("Uniform List, Getting location error: {}.", name).as_str());
-        unsafe { gl::UniformMatrix3fv(*location, 1, gl::FALSE, mat3.as_ptr()); }
+        let Some(location) = self.location(name, &[gl::FLOAT_MAT3]) else { return };
+        unsafe { gl::UniformMatrix3fv(location, 1, gl::FALSE, mat3.as_ptr()); }
     }
 
     pub fn set_mat4(&mut self, name: &str, mat4: &Mat4) {
-        let location = self.uniform_list.get(name).expect(format!("Uniform List, Getting location error: {}.", name).as_str());
-        unsafe { gl::UniformMatrix4fv(*location, 1, gl::FALSE, mat4.as_ptr()); } // burda problem olabilir
+        let Some(location) = self.location(name, &[gl::FLOAT_MAT4]) else { return };
+        unsafe { gl::UniformMatrix4fv(location, 1, gl::FALSE, mat4.as_ptr()); } // burda problem olabilir
     }
 
     pub fn set_texture(&mut self, name: &str, level: GLint) {
-        let location = self.uniform_list.get(name).expect(format!("Uniform List, Getting location error: {}.", name).as_str());
-        unsafe { gl::Uniform1i(*location, level);}
+        let Some(location) = self.location(name, SAMPLER_TYPES) else { return };
+        unsafe { gl::Uniform1i(location, level);}
     }
 
     pub fn id(&self) -> GLuint {
@@ -92,6 +204,7 @@ impl Drop for Program {
 
 pub struct Shader {
     id: GLuint,
+    source_files: Vec<String>
 }
 
 impl Shader {
@@ -116,7 +229,17 @@ impl Shader {
             return Err(error.to_string_lossy().into_owned());
         }
 
-        Ok(Shader{ id })
+        Ok(Shader{ id, source_files: vec![] })
+    }
+
+    // Loads a shader through the preprocessor (#include, injected #defines).
+    pub fn from_file(assets: &AssetResolver, path: &str, stage: ShaderStage, defines: &[(String, String)]) -> Result<Self, AssetError> {
+        let source = preprocess(assets, path, defines)?;
+        let code = CString::new(source.code.as_str()).map_err(|_| AssetError::Compile{ stage, path: path.to_string(), log: "source contains a nul byte".to_string() })?;
+
+        let mut shader = Shader::from_source(&code, stage.gl_enum()).map_err(|log| AssetError::Compile{ stage, path: path.to_string(), log: format_shader_log(path, &source, &log) })?;
+        shader.source_files = source.files;
+        Ok(shader)
     }
 
     pub fn id(&self) -> GLuint {
@@ -130,6 +253,15 @@ impl Drop for Shader {
     }
 }
 
+// Fills a name buffer through one of the glGetActive* calls.
+fn read_resource_name<F: FnOnce(*mut GLsizei, *mut GLchar)>(max_length: GLint, read: F) -> String {
+    let mut buffer: Vec<u8> = vec![0; max_length.max(1) as usize];
+    let mut length: GLsizei = 0;
+    read(&mut length, buffer.as_mut_ptr() as *mut GLchar);
+    buffer.truncate(length.max(0) as usize);
+    String::from_utf8_lossy(&buffer).into_owned()
+}
+
 fn create_whitespace_cstring_with_len(len: usize) -> CString {
     let mut buffer: Vec<u8> = Vec::with_capacity(len + 1);
     buffer.extend([b' '].iter().cycle().take(len));
@@ -137,18 +269,80 @@ fn create_whitespace_cstring_with_len(len: usize) -> CString {
 }
 
 
-pub fn create_program(vertex_path: &str, fragment_path: &str) -> Result<Program, &'static str> {
-    let vertex_shader_source = read_to_string(vertex_path).map_err(|_| "Failed to read vertex shader file")?;
-    let fragment_shader_source = read_to_string(fragment_path).map_err(|_| "Failed to read fragment shader file")?;
+// Rewrites driver log lines such as "0:12(5): error: ..." (Mesa), "0(12) : error ..." (NVIDIA)
+// or "ERROR: 0:12: ..." (AMD/Intel) as "file:line: error: ...", with the line mapped back
+// through includes to the file it was written in.
+fn format_shader_log(path: &str, source: &PreprocessedSource, log: &str) -> String {
+    log.lines()
+        .map(|line| line.trim_matches(|c: char| c.is_whitespace() || c == '\0'))
+        .filter(|line| !line.is_empty())
+        .map(|line| match parse_log_line(line) {
+            Some((line_number, message)) => match source.origin(line_number) {
+                Some((file, original_line)) => format!("{}:{}: {}", file, original_line, message),
+                None => format!("{} (injected #define, line {}): {}", path, line_number, message)
+            },
+            None => format!("{}: {}", path, line)
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
 
-    let vertex_shader = Shader::from_source(&CString::new(vertex_shader_source).unwrap(), gl::VERTEX_SHADER).unwrap();
-    let fragment_shader = Shader::from_source(&CString::new(fragment_shader_source).unwrap(), gl::FRAGMENT_SHADER).unwrap();
+fn parse_log_line(line: &str) -> Option<(u32, String)> {
+    let (severity, rest) = match line.split_once(": ") {
+        Some((severity @ ("ERROR" | "WARNING"), rest)) => (Some(severity), rest),
+        _ => (None, line)
+    };
+
+    let rest = rest.trim_start_matches(|c: char| c.is_ascii_digit()); // source string index
+    let (line_number, message) = if let Some(rest) = rest.strip_prefix(':') {
+        let end = rest.find(|c: char| !c.is_ascii_digit())?;
+        (&rest[..end], rest[end..].trim_start_matches(|c: char| c.is_ascii_digit() || "(): ".contains(c)))
+    } else if let Some(rest) = rest.strip_prefix('(') {
+        let (line_number, message) = rest.split_once(')')?;
+        (line_number, message.trim_start_matches([' ', ':']))
+    } else {
+        return None;
+    };
+
+    let message = match severity {
+        Some(severity) => format!("{}: {}", severity.to_lowercase(), message),
+        None => message.to_string()
+    };
+    Some((line_number.parse().ok()?, message))
+}
 
-    let program = Program::from_shaders(&[vertex_shader, fragment_shader]).unwrap();
+pub fn create_program(assets: &AssetResolver, vertex_path: &str, fragment_path: &str, defines: &[(String, String)]) -> Result<Program, AssetError> {
+    let vertex_shader = Shader::from_file(assets, vertex_path, ShaderStage::Vertex, defines)?;
+    let fragment_shader = Shader::from_file(assets, fragment_path, ShaderStage::Fragment, defines)?;
 
-    Ok(program)
+    Program::from_shaders(&[vertex_shader, fragment_shader]).map_err(|log| AssetError::Link{
+        vertex_path: vertex_path.to_string(),
+        fragment_path: fragment_path.to_string(),
+        log: log.trim_matches(|c: char| c.is_whitespace() || c == '\0').to_string()
+    })
 }
 
+// Solid magenta, uses the common uniforms so it can stand in for any program.
+const ERROR_VERTEX_SHADER: &str = "#version 330 core
+layout (location = 0) in vec3 Position;
+layout (std140) uniform Camera {
+    mat4 u_matrix_projection;
+    mat4 u_matrix_camera;
+};
+uniform mat4 u_matrix_transform;
+void main() { gl_Position = u_matrix_projection * u_matrix_camera * u_matrix_transform * vec4(Position, 1.0); }
+";
+const ERROR_FRAGMENT_SHADER: &str = "#version 330 core
+out vec4 Color;
+void main() { Color = vec4(1.0, 0.0, 1.0, 1.0); }
+";
+
+pub fn create_error_program() -> Program {
+    let vertex_shader = Shader::from_source(&CString::new(ERROR_VERTEX_SHADER).unwrap(), gl::VERTEX_SHADER).expect("Built-in error vertex shader");
+    let fragment_shader = Shader::from_source(&CString::new(ERROR_FRAGMENT_SHADER).unwrap(), gl::FRAGMENT_SHADER).expect("Built-in error fragment shader");
+
+    Program::from_shaders(&[vertex_shader, fragment_shader]).expect("Built-in error program")
+}
 
 pub struct VBO {
     pub id: GLuint
@@ -162,14 +356,31 @@ impl VBO {
         VBO{ id }
     }
 
-    pub fn set(&self, vertices: &Vec<f32>) {
+    pub fn set(&self, vertices: &[f32]) {
         self.bind();
         self.data(vertices);
     }
 
-    fn data(&self, vertices: &Vec<f32>) {
+    fn data(&self, vertices: &[f32]) {
+        unsafe {
+            gl::BufferData(gl::ARRAY_BUFFER, size_of_val(vertices) as GLsizeiptr, vertices.as_ptr() as *const gl::types::GLvoid, gl::DYNAMIC_DRAW);
+        }
+    }
+
+    // Overwrites part of the buffer, offset counted in floats. The buffer must already be large enough.
+    pub fn sub_data(&self, offset: usize, vertices: &[f32]) {
+        self.bind();
+        unsafe {
+            gl::BufferSubData(gl::ARRAY_BUFFER, (offset * size_of::<f32>()) as isize, size_of_val(vertices) as GLsizeiptr, vertices.as_ptr() as *const gl::types::GLvoid);
+        }
+    }
+
+    // Orphans the old storage before uploading, so the driver doesn't stall on data still in use.
+    pub fn stream(&self, vertices: &[f32]) {
+        self.bind();
         unsafe {
-            gl::BufferData(gl::ARRAY_BUFFER, (vertices.len() * size_of::<f32>()) as GLsizeiptr, vertices.as_ptr() as *const gl::types::GLvoid, gl::DYNAMIC_DRAW);
+            gl::BufferData(gl::ARRAY_BUFFER, size_of_val(vertices) as GLsizeiptr, null(), gl::STREAM_DRAW);
+            gl::BufferSubData(gl::ARRAY_BUFFER, 0, size_of_val(vertices) as GLsizeiptr, vertices.as_ptr() as *const gl::types::GLvoid);
         }
     }
 
@@ -206,14 +417,22 @@ impl IBO {
         IBO{ id }
     }
 
-    pub fn set(&self, indices: &Vec<u32>) {
+    pub fn set(&self, indices: &[u32]) {
         self.bind();
         self.data(indices);
     }
 
-    fn data(&self, indices: &Vec<u32>) {
+    fn data(&self, indices: &[u32]) {
+        unsafe {
+            gl::BufferData(gl::ELEMENT_ARRAY_BUFFER, size_of_val(indices) as GLsizeiptr, indices.as_ptr() as *const gl::types::GLvoid, gl::DYNAMIC_DRAW);
+        }
+    }
+
+    // Offset counted in indices. Binds to the current VAO, like set().
+    pub fn sub_data(&self, offset: usize, indices: &[u32]) {
+        self.bind();
         unsafe {
-            gl::BufferData(gl::ELEMENT_ARRAY_BUFFER, (indices.len() * size_of::<u32>()) as GLsizeiptr, indices.as_ptr() as *const gl::types::GLvoid, gl::DYNAMIC_DRAW);
+            gl::BufferSubData(gl::ELEMENT_ARRAY_BUFFER, (offset * size_of::<u32>()) as isize, size_of_val(indices) as GLsizeiptr, indices.as_ptr() as *const gl::types::GLvoid);
         }
     }
 
@@ -238,7 +457,61 @@ impl Drop for IBO {
 }
 
 
-///////////////////////////////////////////////////////////// EDIT LATER
+// std140 uniform block storage, attached to a binding point shared by all programs.
+pub struct UBO {
+    pub id: GLuint
+}
+
+impl UBO {
+    pub fn generate(size: usize, binding: GLuint) -> Self {
+        let mut id: GLuint = 0;
+        unsafe {
+            gl::GenBuffers(1, &mut id);
+            gl::BindBuffer(gl::UNIFORM_BUFFER, id);
+            gl::BufferData(gl::UNIFORM_BUFFER, size as GLsizeiptr, null(), gl::DYNAMIC_DRAW);
+            gl::BindBufferBase(gl::UNIFORM_BUFFER, binding, id);
+            gl::BindBuffer(gl::UNIFORM_BUFFER, 0);
+        }
+
+        UBO{ id }
+    }
+
+    pub fn set_mat4(&self, offset: usize, mat4: &Mat4) {
+        self.sub_data(offset, mat4.as_slice());
+    }
+
+    pub fn set_vec4(&self, offset: usize, vec4: &Vec4) {
+        self.sub_data(offset, vec4.as_slice());
+    }
+
+    fn sub_data(&self, offset: usize, data: &[f32]) {
+        self.bind();
+        unsafe {
+            gl::BufferSubData(gl::UNIFORM_BUFFER, offset as isize, size_of_val(data) as GLsizeiptr, data.as_ptr() as *const gl::types::GLvoid);
+        }
+    }
+
+    pub fn bind(&self) {
+        unsafe { gl::BindBuffer(gl::UNIFORM_BUFFER, self.id); }
+    }
+
+    pub fn unbind(&self) {
+        unsafe { gl::BindBuffer(gl::UNIFORM_BUFFER, 0); }
+    }
+
+    pub fn delete(&self) {
+        unsafe { gl::DeleteBuffers(1, &self.id); }
+    }
+}
+
+impl Drop for UBO {
+    fn drop(&mut self) {
+        self.unbind();
+        self.delete();
+    }
+}
+
+
 pub struct VAO {
     pub id: GLuint
 }
@@ -251,23 +524,23 @@ impl VAO {
         VAO { id }
     }
 
-    pub fn set(&self, two_dimension: bool) {
+    // Describes the currently bound ARRAY_BUFFER. Call once per buffer, e.g. vertices then instances.
+    pub fn set(&self, layout: &VertexLayout) {
         self.bind();
-        self.setup(two_dimension);
+        self.setup(layout);
     }
 
-    fn setup(&self, two_dimension: bool) {
-        if two_dimension {
-            unsafe {
-                gl::EnableVertexAttribArray(0);
-                gl::VertexAttribPointer(0, 2, gl::FLOAT, gl::FALSE, (2 * size_of::<f32>()) as GLint, null());
-            }
-        } else {
+    fn setup(&self, layout: &VertexLayout) {
+        for attribute in &layout.attributes {
+            let offset = attribute.offset as *const gl::types::GLvoid;
             unsafe {
-                gl::EnableVertexAttribArray(0);
-                gl::VertexAttribPointer(0, 3, gl::FLOAT, gl::FALSE, (5 * size_of::<f32>()) as GLint, null());
-                gl::EnableVertexAttribArray(1);
-                gl::VertexAttribPointer(1, 2, gl::FLOAT, gl::FALSE, (5 * size_of::<f32>()) as GLint, (3 * size_of::<f32>()) as *const std::os::raw::c_void);
+                gl::EnableVertexAttribArray(attribute.location);
+                if attribute.integer {
+                    gl::VertexAttribIPointer(attribute.location, attribute.components, attribute.gl_type, layout.stride as GLsizei, offset);
+                } else {
+                    gl::VertexAttribPointer(attribute.location, attribute.components, attribute.gl_type, GLboolean::from(attribute.normalized), layout.stride as GLsizei, offset);
+                }
+                gl::VertexAttribDivisor(attribute.location, attribute.divisor);
             }
         }
     }
@@ -0,0 +1,115 @@
+use crate::renderer::FogMode;
+use crate::texture::{TextureFilter, TextureFiltering};
+use crate::world_clock::{parse_time, DEFAULT_DAY_LENGTH, DEFAULT_TIME};
+use std::env;
+use std::path::PathBuf;
+
+// Command line options:
+//   --headless       render offscreen through a hidden window, no display needed
+//   --simulate       run the simulation without SDL or OpenGL
+//   --frames <n>     stop after n frames (headless defaults to 1)
+//   --screenshot     save the last frame to ./screenshots before exiting
+//   --screenshot-scale <n>   resolution multiplier for high-res captures (F3), default 2
+//   --golden         render the golden scenes and compare them with ./tests/golden
+//   --golden-update  render the golden scenes and overwrite the reference images
+//   --no-ao          mesh blocks without ambient occlusion (toggle at runtime with F4)
+//   --time <t>       starting time of day: hours, hh:mm, sunrise, noon, sunset or midnight
+//   --day-length <s> length of a full day in seconds, default 600
+//   --seed <n>       scatter extra blocks over the floor, 0 (default) keeps the world as built
+//   --fog <mode>     off, linear (default) or exponential distance fog
+//   --filter <mode>  block texture filtering: nearest, pixelated (default) or trilinear (cycle at runtime with F9)
+//   --anisotropy <n> anisotropic filtering level where supported, 1 turns it off, default 8
+//   --assets <dir>   directory with the builtin shaders/ and textures/, overrides ABYSSAL_ECLIPSE_ASSETS and the search
+//   --pack <path>    add a resource pack (directory or zip with a pack.txt), repeatable, later packs override earlier ones
+//   --no-srgb        treat textures as linear and skip the sRGB output encoding, the old gamma space look (toggle with F10)
+pub struct Options {
+    pub headless: bool,
+    pub simulate: bool,
+    pub frames: Option<u32>,
+    pub screenshot: bool,
+    pub screenshot_scale: u32,
+    pub golden: bool,
+    pub golden_update: bool,
+    pub ambient_occlusion: bool,
+    pub time: f32,
+    pub day_length: f32,
+    pub seed: u32,
+    pub fog_mode: FogMode,
+    pub srgb: bool,
+    pub texture_filtering: TextureFiltering,
+    pub packs: Vec<PathBuf>,
+    pub assets: Option<PathBuf>
+}
+
+impl Options {
+    pub fn from_args() -> Result<Self, String> {
+        Self::parse(env::args().skip(1))
+    }
+
+    pub fn parse<I: Iterator<Item = String>>(mut args: I) -> Result<Self, String> {
+        let mut options = Options{ headless: false, simulate: false, frames: None, screenshot: false, screenshot_scale: 2, golden: false, golden_update: false, ambient_occlusion: true, time: DEFAULT_TIME, day_length: DEFAULT_DAY_LENGTH, seed: 0, fog_mode: FogMode::Linear, srgb: true, texture_filtering: TextureFiltering::new(), packs: vec![], assets: None };
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--headless" => options.headless = true,
+                "--simulate" => options.simulate = true,
+                "--frames" => {
+                    let value = args.next().ok_or("--frames needs a value")?;
+                    options.frames = Some(value.parse().map_err(|_| format!("Invalid frame count: {}", value))?);
+                },
+                "--screenshot" => options.screenshot = true,
+                "--screenshot-scale" => {
+                    let value = args.next().ok_or("--screenshot-scale needs a value")?;
+                    options.screenshot_scale = value.parse().ok().filter(|scale| *scale > 0).ok_or(format!("Invalid screenshot scale: {}", value))?;
+                },
+                "--golden" => options.golden = true,
+                "--golden-update" => options.golden_update = true,
+                "--no-ao" => options.ambient_occlusion = false,
+                "--no-srgb" => options.srgb = false,
+                "--assets" => options.assets = Some(PathBuf::from(args.next().ok_or("--assets needs a directory")?)),
+                "--pack" => options.packs.push(PathBuf::from(args.next().ok_or("--pack needs a path")?)),
+                "--time" => {
+                    let value = args.next().ok_or("--time needs a value")?;
+                    options.time = parse_time(&value).ok_or(format!("Invalid time: {}", value))?;
+                },
+                "--fog" => {
+                    options.fog_mode = match args.next().as_deref() {
+                        Some("off") => FogMode::Off,
+                        Some("linear") => FogMode::Linear,
+                        Some("exponential") => FogMode::Exponential,
+                        Some(value) => return Err(format!("Invalid fog mode: {}", value)),
+                        None => return Err("--fog needs a value".to_string())
+                    };
+                },
+                "--filter" => {
+                    options.texture_filtering.filter = match args.next().as_deref() {
+                        Some("nearest") => TextureFilter::Nearest,
+                        Some("pixelated") => TextureFilter::Pixelated,
+                        Some("trilinear") => TextureFilter::Trilinear,
+                        Some(value) => return Err(format!("Invalid texture filter: {}", value)),
+                        None => return Err("--filter needs a value".to_string())
+                    };
+                },
+                "--anisotropy" => {
+                    let value = args.next().ok_or("--anisotropy needs a value")?;
+                    options.texture_filtering.anisotropy = value.parse().ok().filter(|level: &f32| *level >= 1.0).ok_or(format!("Invalid anisotropy: {}", value))?;
+                },
+                "--seed" => {
+                    let value = args.next().ok_or("--seed needs a value")?;
+                    options.seed = value.parse().map_err(|_| format!("Invalid seed: {}", value))?;
+                },
+                "--day-length" => {
+                    let value = args.next().ok_or("--day-length needs a value")?;
+                    options.day_length = value.parse().ok().filter(|length: &f32| *length > 0.0).ok_or(format!("Invalid day length: {}", value))?;
+                },
+                _ => return Err(format!("Unknown argument: {}", arg))
+            }
+        }
+
+        if options.headless && options.frames.is_none() {
+            options.frames = Some(1);
+        }
+
+        Ok(options)
+    }
+}
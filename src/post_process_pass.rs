@@ -0,0 +1,169 @@
+use crate::mesh::Mesh;
+use crate::render_pass::{FrameContext, RenderPass, RenderStage, RenderState};
+use crate::render_target::RenderTarget;
+use crate::shader_manager::{ProgramHandle, ShaderManager};
+use crate::vertex_layout::VertexLayout;
+use gl::types::{GLsizei, GLuint};
+use nalgebra_glm::vec2;
+
+const BLUR_PASSES: usize = 4; // horizontal + vertical pairs over the half resolution bloom image
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Tonemapping {
+    None,     // clamps, mostly for comparing against the others
+    Reinhard,
+    Aces
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Antialiasing {
+    None,
+    Msaa, // multisampled scene target, resolved before post-processing
+    Fxaa  // single sampled scene target, FXAA on the tonemapped image
+}
+
+#[derive(Clone, Copy)]
+pub struct PostProcessSettings {
+    pub tonemapping: Tonemapping,
+    pub exposure: f32,
+    pub srgb: bool, // the scene is in linear space and gets sRGB encoded on output
+    pub gamma: f32,  // only without sRGB, for the old gamma space textures
+    pub bloom: bool,
+    pub bloom_threshold: f32, // HDR brightness where bloom starts, only emissive blocks go past 1
+    pub bloom_intensity: f32,
+    pub antialiasing: Antialiasing
+}
+
+impl PostProcessSettings {
+    pub fn new() -> Self {
+        PostProcessSettings{
+            tonemapping: Tonemapping::Aces,
+            exposure: 1.0,
+            srgb: true,
+            gamma: 1.0,
+            bloom: true,
+            bloom_threshold: 1.0,
+            bloom_intensity: 0.6,
+            antialiasing: Antialiasing::Msaa
+        }
+    }
+}
+
+// Turns the resolved HDR scene into the final image in the output framebuffer:
+// bloom (bright pass + gaussian blur), tonemapping and sRGB encoding, then FXAA if enabled.
+pub struct PostProcessPass {
+    bloom_program: ProgramHandle,
+    blur_program: ProgramHandle,
+    composite_program: ProgramHandle,
+    fxaa_program: ProgramHandle,
+    fullscreen_triangle: Mesh,
+    bloom_targets: Vec<RenderTarget>,
+    ldr_target: Option<RenderTarget>
+}
+
+impl PostProcessPass {
+    pub fn new(shader_manager: &mut ShaderManager) -> Self {
+        let bloom_program = shader_manager.load_or_fallback("shaders/post_vertex.glsl", "shaders/bloom_fragment.glsl");
+        let blur_program = shader_manager.load_or_fallback("shaders/post_vertex.glsl", "shaders/blur_fragment.glsl");
+        let composite_program = shader_manager.load_or_fallback("shaders/post_vertex.glsl", "shaders/composite_fragment.glsl");
+        let fxaa_program = shader_manager.load_or_fallback("shaders/post_vertex.glsl", "shaders/fxaa_fragment.glsl");
+
+        // One triangle covering the screen, no seam along a diagonal.
+        let fullscreen_triangle = Mesh::new(&[-1.0, -1.0, 3.0, -1.0, -1.0, 3.0], None, VertexLayout::new().float(0, 2));
+
+        PostProcessPass{ bloom_program, blur_program, composite_program, fxaa_program, fullscreen_triangle, bloom_targets: vec![], ldr_target: None }
+    }
+
+    // Intermediate targets follow the output size, recreated only when it changes.
+    fn resize(&mut self, width: u32, height: u32) {
+        let (bloom_width, bloom_height) = ((width / 2).max(1), (height / 2).max(1));
+        if self.bloom_targets.first().is_none_or(|target| (target.get_width(), target.get_height()) != (bloom_width, bloom_height)) {
+            self.bloom_targets = (0..2).map(|_| RenderTarget::generate(bloom_width, bloom_height, gl::RGBA16F, 0, false).unwrap()).collect();
+        }
+        if self.ldr_target.as_ref().is_none_or(|target| (target.get_width(), target.get_height()) != (width, height)) {
+            self.ldr_target = Some(RenderTarget::generate(width, height, gl::RGBA8, 0, false).unwrap());
+        }
+    }
+}
+
+impl RenderPass for PostProcessPass {
+    fn get_stage(&self) -> RenderStage {
+        RenderStage::PostProcess
+    }
+
+    fn get_state(&self) -> RenderState {
+        RenderState::post_process()
+    }
+
+    fn draw(&mut self, context: &mut FrameContext) {
+        let settings = context.post_process;
+        let [x, y, width, height] = context.output_viewport;
+        self.resize(width as u32, height as u32);
+
+        if settings.bloom {
+            let bloom_program = context.shader_manager.get(self.bloom_program);
+            bloom_program.use_program();
+            bloom_program.set_texture("u_scene", 0);
+            bloom_program.set_float("u_threshold", settings.bloom_threshold);
+            self.bloom_targets[0].bind();
+            context.texture.activate_texture(gl::TEXTURE0, context.scene_texture);
+            self.fullscreen_triangle.draw(gl::TRIANGLES);
+
+            let blur_program = context.shader_manager.get(self.blur_program);
+            blur_program.use_program();
+            blur_program.set_texture("u_image", 0);
+            let texel = vec2(1.0 / self.bloom_targets[0].get_width() as f32, 1.0 / self.bloom_targets[0].get_height() as f32);
+            for _ in 0..BLUR_PASSES {
+                for (source, destination, direction) in [(0, 1, vec2(texel.x, 0.0)), (1, 0, vec2(0.0, texel.y))] {
+                    self.bloom_targets[destination].bind();
+                    blur_program.set_vec2("u_direction", &direction);
+                    context.texture.activate_texture(gl::TEXTURE0, self.bloom_targets[source].get_color_texture());
+                    self.fullscreen_triangle.draw(gl::TRIANGLES);
+                }
+            }
+        }
+
+        let fxaa = settings.antialiasing == Antialiasing::Fxaa;
+        let ldr_target = self.ldr_target.as_ref().unwrap();
+        if fxaa {
+            ldr_target.bind();
+        } else {
+            bind_output(context.output_framebuffer, context.output_viewport);
+        }
+
+        let composite_program = context.shader_manager.get(self.composite_program);
+        composite_program.use_program();
+        composite_program.set_texture("u_scene", 0);
+        composite_program.set_texture("u_bloom", 1);
+        composite_program.set_float("u_bloom_intensity", if settings.bloom { settings.bloom_intensity } else { 0.0 });
+        composite_program.set_float("u_exposure", settings.exposure);
+        composite_program.set_float("u_gamma", settings.gamma);
+        composite_program.set_int("u_srgb", settings.srgb as i32);
+        composite_program.set_int("u_tonemapping", settings.tonemapping as i32);
+        context.texture.activate_texture(gl::TEXTURE0, context.scene_texture);
+        context.texture.activate_texture(gl::TEXTURE1, self.bloom_targets[0].get_color_texture());
+        self.fullscreen_triangle.draw(gl::TRIANGLES);
+
+        if fxaa {
+            bind_output(context.output_framebuffer, context.output_viewport);
+            let fxaa_program = context.shader_manager.get(self.fxaa_program);
+            fxaa_program.use_program();
+            fxaa_program.set_texture("u_image", 0);
+            fxaa_program.set_vec2("u_texel", &vec2(1.0 / width as f32, 1.0 / height as f32));
+            context.texture.activate_texture(gl::TEXTURE0, ldr_target.get_color_texture());
+            self.fullscreen_triangle.draw(gl::TRIANGLES);
+        }
+
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::Viewport(x, y, width, height);
+        }
+    }
+}
+
+fn bind_output(framebuffer: GLuint, [x, y, width, height]: [i32; 4]) {
+    unsafe {
+        gl::BindFramebuffer(gl::FRAMEBUFFER, framebuffer);
+        gl::Viewport(x, y, width as GLsizei, height as GLsizei);
+    }
+}
@@ -0,0 +1,105 @@
+use crate::camera::Camera;
+use crate::chunk_renderer::ChunkRenderer;
+use crate::instanced_renderer::InstancedRenderer;
+use crate::lighting::Lighting;
+use crate::post_process_pass::PostProcessSettings;
+use crate::shader_manager::ShaderManager;
+use crate::texture::Texture;
+use gl::types::{GLboolean, GLenum, GLint, GLuint};
+
+// Passes run in this order, passes of the same stage in the order they were added.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum RenderStage {
+    Shadow,
+    Opaque,
+    Skybox,
+    Transparent,
+    PostProcess, // reads the resolved HDR scene, writes the output framebuffer
+    Overlay      // drawn into the output framebuffer after tonemapping
+}
+
+// The complete fixed-function state a pass draws with. Every field is applied before
+// each pass, so nothing a pass changes can leak into the next one.
+#[derive(Clone, Copy)]
+pub struct RenderState {
+    pub depth_test: bool,
+    pub depth_write: bool,
+    pub depth_func: GLenum,
+    pub cull_face: bool,
+    pub blend: Option<(GLenum, GLenum)>, // (source, destination) factors
+    pub line_width: f32
+}
+
+impl RenderState {
+    // Depth only, into the pass's own target. Back faces are kept so thin geometry still casts.
+    pub fn shadow() -> Self {
+        RenderState{ depth_test: true, depth_write: true, depth_func: gl::LESS, cull_face: false, blend: None, line_width: 1.0 }
+    }
+
+    pub fn opaque() -> Self {
+        RenderState{ depth_test: true, depth_write: true, depth_func: gl::LESS, cull_face: true, blend: None, line_width: 1.0 }
+    }
+
+    // Drawn after the opaque pass at the far plane, only where nothing else was drawn.
+    pub fn skybox() -> Self {
+        RenderState{ depth_test: true, depth_write: false, depth_func: gl::LEQUAL, cull_face: false, blend: None, line_width: 1.0 }
+    }
+
+    pub fn transparent() -> Self {
+        RenderState{ depth_test: true, depth_write: false, depth_func: gl::LESS, cull_face: true, blend: Some((gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA)), line_width: 1.0 }
+    }
+
+    pub fn overlay() -> Self {
+        RenderState{ depth_test: false, depth_write: false, depth_func: gl::LESS, cull_face: false, blend: Some((gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA)), line_width: 1.0 }
+    }
+
+    pub fn post_process() -> Self {
+        RenderState{ depth_test: false, depth_write: false, depth_func: gl::LESS, cull_face: false, blend: None, line_width: 1.0 }
+    }
+
+    pub fn apply(&self) {
+        unsafe {
+            set_capability(gl::DEPTH_TEST, self.depth_test);
+            gl::DepthMask(GLboolean::from(self.depth_write));
+            gl::DepthFunc(self.depth_func);
+            set_capability(gl::CULL_FACE, self.cull_face);
+            match self.blend {
+                Some((source, destination)) => {
+                    gl::Enable(gl::BLEND);
+                    gl::BlendFunc(source, destination);
+                },
+                None => gl::Disable(gl::BLEND)
+            }
+            gl::LineWidth(self.line_width);
+        }
+    }
+}
+
+unsafe fn set_capability(capability: GLenum, enabled: bool) {
+    if enabled { gl::Enable(capability); } else { gl::Disable(capability); }
+}
+
+// Shared, per-frame data handed to every pass.
+pub struct FrameContext<'a> {
+    pub camera: &'a mut Camera,
+    pub shader_manager: &'a mut ShaderManager,
+    pub texture: &'a Texture,
+    pub chunk_renderer: &'a mut ChunkRenderer,
+    pub instanced_renderer: &'a InstancedRenderer,
+    pub lighting: &'a Lighting,
+    pub post_process: &'a PostProcessSettings,
+    pub scene_texture: GLuint, // resolved HDR colour, valid from the post-process stage on
+    pub output_framebuffer: GLuint,
+    pub output_viewport: [GLint; 4]
+}
+
+pub trait RenderPass {
+    fn get_stage(&self) -> RenderStage;
+
+    fn get_state(&self) -> RenderState;
+
+    fn draw(&mut self, context: &mut FrameContext);
+
+    // Called after the resource packs changed, passes that loaded textures load them again.
+    fn reload_textures(&mut self, _texture: &mut Texture) {}
+}
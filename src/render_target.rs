@@ -0,0 +1,119 @@
+use gl::types::{GLenum, GLint, GLsizei, GLuint};
+
+// Offscreen colour (+ optional depth) target in any colour format. Multisampled targets store colour
+// in a renderbuffer and have to be resolved into a single sampled one before they can be read.
+pub struct RenderTarget {
+    pub id: GLuint,
+    color: GLuint, // texture, or renderbuffer when multisampled
+    depth_renderbuffer: Option<GLuint>,
+    width: u32,
+    height: u32,
+    samples: u8
+}
+
+impl RenderTarget {
+    pub fn generate(width: u32, height: u32, internal_format: GLenum, samples: u8, with_depth: bool) -> Result<Self, &'static str> {
+        let mut id: GLuint = 0;
+        let mut color: GLuint = 0;
+        let mut depth_renderbuffer = None;
+        let (width_gl, height_gl) = (width as GLsizei, height as GLsizei);
+
+        unsafe {
+            gl::GenFramebuffers(1, &mut id);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, id);
+
+            if samples > 0 {
+                gl::GenRenderbuffers(1, &mut color);
+                gl::BindRenderbuffer(gl::RENDERBUFFER, color);
+                gl::RenderbufferStorageMultisample(gl::RENDERBUFFER, samples as GLsizei, internal_format, width_gl, height_gl);
+                gl::FramebufferRenderbuffer(gl::FRAMEBUFFER, gl::COLOR_ATTACHMENT0, gl::RENDERBUFFER, color);
+            } else {
+                gl::GenTextures(1, &mut color);
+                gl::BindTexture(gl::TEXTURE_2D, color);
+                gl::TexStorage2D(gl::TEXTURE_2D, 1, internal_format, width_gl, height_gl);
+                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as GLint);
+                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as GLint);
+                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as GLint);
+                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as GLint);
+                gl::BindTexture(gl::TEXTURE_2D, 0);
+                gl::FramebufferTexture2D(gl::FRAMEBUFFER, gl::COLOR_ATTACHMENT0, gl::TEXTURE_2D, color, 0);
+            }
+
+            if with_depth {
+                let mut depth: GLuint = 0;
+                gl::GenRenderbuffers(1, &mut depth);
+                gl::BindRenderbuffer(gl::RENDERBUFFER, depth);
+                gl::RenderbufferStorageMultisample(gl::RENDERBUFFER, samples as GLsizei, gl::DEPTH24_STENCIL8, width_gl, height_gl);
+                gl::FramebufferRenderbuffer(gl::FRAMEBUFFER, gl::DEPTH_STENCIL_ATTACHMENT, gl::RENDERBUFFER, depth);
+                depth_renderbuffer = Some(depth);
+            }
+            gl::BindRenderbuffer(gl::RENDERBUFFER, 0);
+        }
+
+        let target = RenderTarget{ id, color, depth_renderbuffer, width, height, samples };
+
+        let status: GLenum = unsafe { gl::CheckFramebufferStatus(gl::FRAMEBUFFER) };
+        unsafe { gl::BindFramebuffer(gl::FRAMEBUFFER, 0); }
+
+        if status != gl::FRAMEBUFFER_COMPLETE {
+            return Err("Render target is not complete");
+        }
+
+        Ok(target)
+    }
+
+    pub fn bind(&self) {
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.id);
+            gl::Viewport(0, 0, self.width as GLsizei, self.height as GLsizei);
+        }
+    }
+
+    // Averages the samples of a multisampled target into a single sampled one of the same size.
+    pub fn resolve_into(&self, target: &RenderTarget) {
+        unsafe {
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, self.id);
+            gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, target.id);
+            gl::BlitFramebuffer(0, 0, self.width as GLint, self.height as GLint, 0, 0, target.width as GLint, target.height as GLint, gl::COLOR_BUFFER_BIT, gl::NEAREST);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+        }
+    }
+
+    // Not available on multisampled targets.
+    pub fn get_color_texture(&self) -> GLuint {
+        assert_eq!(self.samples, 0, "Multisampled render targets have to be resolved before sampling");
+        self.color
+    }
+
+    pub fn get_width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn get_height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn get_samples(&self) -> u8 {
+        self.samples
+    }
+
+    pub fn delete(&self) {
+        unsafe {
+            gl::DeleteFramebuffers(1, &self.id);
+            if self.samples > 0 {
+                gl::DeleteRenderbuffers(1, &self.color);
+            } else {
+                gl::DeleteTextures(1, &self.color);
+            }
+            if let Some(depth) = &self.depth_renderbuffer {
+                gl::DeleteRenderbuffers(1, depth);
+            }
+        }
+    }
+}
+
+impl Drop for RenderTarget {
+    fn drop(&mut self) {
+        self.delete();
+    }
+}
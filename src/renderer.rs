@@ -0,0 +1,259 @@
+use crate::block_pass::BlockPass;
+use crate::camera::Camera;
+use crate::chunk_renderer::ChunkRenderer;
+use crate::error::AssetError;
+use crate::crosshair_pass::CrosshairPass;
+use crate::instance_pass::InstancePass;
+use crate::instanced_renderer::{Instance, InstancedRenderer};
+use crate::lighting::{Lighting, LightingBuffer};
+use crate::mesh::Mesh;
+use crate::object::{CAMERA_BLOCK_BINDING, UBO};
+use crate::post_process_pass::{Antialiasing, PostProcessPass, PostProcessSettings};
+use crate::render_pass::{FrameContext, RenderPass, RenderStage, RenderState};
+use crate::render_target::RenderTarget;
+use crate::resource_pack::{AssetResolver, ResourcePack};
+use crate::shader_manager::ShaderManager;
+use crate::shadow_map::SHADOW_CASCADES;
+use crate::shadow_pass::ShadowPass;
+use crate::shape_data::ShapeData;
+use crate::skybox_pass::SkyboxPass;
+use crate::texture::{Texture, TextureFiltering};
+use crate::transparent_pass::TransparentPass;
+use crate::win_sdl::MSAA_SAMPLES;
+use crate::world::{ChunkPosition, World};
+use gl::types::{GLint, GLuint};
+use nalgebra_glm::{vec4, Mat4, Vec4};
+use std::path::PathBuf;
+use std::rc::Rc;
+
+const FOG_START: f32 = 0.5; // fractions of the far plane distance
+const FOG_END: f32 = 0.95;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum FogMode {
+    Off,
+    Linear,
+    Exponential
+}
+
+pub struct Renderer {
+    assets: Rc<AssetResolver>,
+    texture: Texture,
+    shader_manager: ShaderManager,
+    camera_buffer: UBO,
+    fog_mode: FogMode,
+    lighting: Lighting,
+    lighting_buffer: LightingBuffer,
+    chunk_renderer: ChunkRenderer,
+    instanced_renderer: InstancedRenderer,
+    post_process: PostProcessSettings,
+    scene_target: Option<RenderTarget>, // multisampled, only with MSAA
+    hdr_target: Option<RenderTarget>,
+
+    passes: Vec<Box<dyn RenderPass>>
+}
+
+impl Renderer {
+    // Without sRGB, colour textures are used as stored and lighting happens in gamma space, for comparison.
+    pub fn new(assets: AssetResolver, srgb: bool) -> Self {
+        let assets = Rc::new(assets);
+        let mut texture: Texture = Texture::new(assets.clone(), srgb);
+
+        let mut shader_manager = ShaderManager::new(assets.clone());
+        shader_manager.set_define("MSAA_SAMPLES", &MSAA_SAMPLES.to_string());
+        shader_manager.set_define("SHADOW_CASCADES", &SHADOW_CASCADES.to_string());
+
+        let camera_buffer = UBO::generate(2 * size_of::<Mat4>() + size_of::<Vec4>(), CAMERA_BLOCK_BINDING); // Camera block in common.glsl
+
+        //let (rand_vertices, rand_indices) = create_perlin_noise_grid_with_tex_coords(GRID_SIZE);
+
+        let chunk_renderer = ChunkRenderer::new(&mut texture);
+        let instanced_renderer = InstancedRenderer::new(Mesh::new(&ShapeData::get_cube_vertices(), Some(&ShapeData::get_cube_indices()), ShapeData::get_cube_layout()));
+
+        let shadow_pass = ShadowPass::new(&mut shader_manager);
+        let block_pass = BlockPass::new(&mut shader_manager);
+        let instance_pass = InstancePass::new(&mut shader_manager);
+        let skybox_pass = SkyboxPass::new(&mut shader_manager, &mut texture);
+        let transparent_pass = TransparentPass::new(&mut shader_manager);
+        let post_process_pass = PostProcessPass::new(&mut shader_manager);
+        let crosshair_pass = CrosshairPass::new(&mut shader_manager);
+
+        let mut renderer = Renderer{
+            assets,
+            texture,
+            shader_manager,
+            camera_buffer,
+            fog_mode: FogMode::Linear,
+            lighting: Lighting::new(),
+            lighting_buffer: LightingBuffer::new(),
+            chunk_renderer,
+            instanced_renderer,
+            post_process: PostProcessSettings{ srgb, ..PostProcessSettings::new() },
+            scene_target: None,
+            hdr_target: None,
+            passes: vec![]
+        };
+        renderer.add_pass(Box::new(shadow_pass));
+        renderer.add_pass(Box::new(block_pass));
+        renderer.add_pass(Box::new(instance_pass));
+        renderer.add_pass(Box::new(skybox_pass));
+        renderer.add_pass(Box::new(transparent_pass));
+        renderer.add_pass(Box::new(post_process_pass));
+        renderer.add_pass(Box::new(crosshair_pass));
+        renderer
+    }
+
+    // Inserts after every pass of the same or an earlier stage, so the order passes are added in doesn't matter.
+    pub fn add_pass(&mut self, pass: Box<dyn RenderPass>) {
+        let index = self.passes.partition_point(|other| other.get_stage() <= pass.get_stage());
+        self.passes.insert(index, pass);
+    }
+
+    pub fn set_fog_mode(&mut self, fog_mode: FogMode) {
+        self.fog_mode = fog_mode;
+    }
+
+    pub fn get_post_process(&self) -> PostProcessSettings {
+        self.post_process
+    }
+
+    // Switching sRGB re-uploads every texture in the new format.
+    pub fn set_post_process(&mut self, post_process: PostProcessSettings) {
+        let srgb_changed = post_process.srgb != self.post_process.srgb;
+        self.post_process = post_process;
+        if srgb_changed {
+            self.texture.set_srgb(post_process.srgb);
+            self.reload_textures();
+        }
+    }
+
+    pub fn get_resource_packs(&self) -> &[ResourcePack] {
+        self.assets.get_packs()
+    }
+
+    // Replaces the pack stack on top of the builtin assets and reloads every shader and texture.
+    // On error the current packs stay in use.
+    pub fn set_resource_packs(&mut self, pack_paths: &[PathBuf]) -> Result<(), AssetError> {
+        self.assets = Rc::new(AssetResolver::new(self.assets.get_builtin().clone(), pack_paths)?);
+        self.shader_manager.set_assets(self.assets.clone());
+        self.texture.set_assets(self.assets.clone());
+        self.reload_textures();
+        Ok(())
+    }
+
+    fn reload_textures(&mut self) {
+        self.chunk_renderer.reload_textures(&mut self.texture);
+        for pass in self.passes.iter_mut() {
+            pass.reload_textures(&mut self.texture);
+        }
+    }
+
+    pub fn get_texture_filtering(&self) -> TextureFiltering {
+        self.texture.get_filtering()
+    }
+
+    pub fn set_texture_filtering(&mut self, filtering: TextureFiltering) {
+        self.texture.set_filtering(filtering);
+        self.texture.apply_filtering(self.chunk_renderer.get_block_textures());
+    }
+
+    pub fn set_lighting(&mut self, lighting: Lighting) {
+        self.lighting = lighting;
+    }
+
+    // Advances everything that animates on its own, once per frame before render.
+    pub fn update(&mut self, delta_time: f32) {
+        self.chunk_renderer.update_animations(&self.texture, delta_time);
+    }
+
+    pub fn set_world(&mut self, world: &World) {
+        self.chunk_renderer.set_world(world);
+    }
+
+    pub fn set_ambient_occlusion(&mut self, world: &World, enabled: bool) {
+        self.chunk_renderer.set_ambient_occlusion(world, enabled);
+    }
+
+    pub fn update_chunk(&mut self, world: &World, chunk_position: ChunkPosition) {
+        self.chunk_renderer.update_chunk(world, chunk_position);
+    }
+
+    // Everything drawn as an instanced cube, replaced as a whole each time.
+    pub fn set_instances(&mut self, instances: &[Instance]) {
+        self.instanced_renderer.set_instances(instances);
+    }
+
+    // Scene targets match the output size and are recreated when it or the antialiasing mode changes.
+    fn resize_targets(&mut self, width: u32, height: u32) {
+        let samples = if self.post_process.antialiasing == Antialiasing::Msaa { MSAA_SAMPLES } else { 0 };
+        let matches = |target: &RenderTarget, samples: u8| (target.get_width(), target.get_height(), target.get_samples()) == (width, height, samples);
+
+        if !self.hdr_target.as_ref().is_some_and(|target| matches(target, 0)) {
+            self.hdr_target = Some(RenderTarget::generate(width, height, gl::RGBA16F, 0, true).unwrap());
+        }
+        if samples == 0 {
+            self.scene_target = None;
+        } else if !self.scene_target.as_ref().is_some_and(|target| matches(target, samples)) {
+            self.scene_target = Some(RenderTarget::generate(width, height, gl::RGBA16F, samples, true).unwrap());
+        }
+    }
+
+    // Draws one frame into whatever framebuffer is currently bound. The scene goes into an HDR target
+    // first, the post-processing pass writes the result into the bound framebuffer, overlays go on top.
+    pub fn render(&mut self, camera: &mut Camera) {
+        self.shader_manager.reload_changed();
+
+        self.camera_buffer.set_mat4(0, &camera.get_projection());
+        self.camera_buffer.set_mat4(size_of::<Mat4>(), &camera.get_camera_look_at());
+        let far_plane = camera.get_far_plane();
+        let fog = vec4(far_plane * FOG_START, far_plane * FOG_END, self.fog_mode as u32 as f32, 0.0);
+        self.camera_buffer.set_vec4(2 * size_of::<Mat4>(), &fog);
+        self.lighting_buffer.upload(&self.lighting, self.post_process.srgb);
+
+        let mut output_framebuffer: GLint = 0;
+        let mut output_viewport: [GLint; 4] = [0; 4];
+        unsafe {
+            gl::GetIntegerv(gl::DRAW_FRAMEBUFFER_BINDING, &mut output_framebuffer);
+            gl::GetIntegerv(gl::VIEWPORT, output_viewport.as_mut_ptr());
+        }
+        self.resize_targets(output_viewport[2] as u32, output_viewport[3] as u32);
+
+        let hdr_target = self.hdr_target.as_ref().unwrap();
+        self.scene_target.as_ref().unwrap_or(hdr_target).bind();
+
+        RenderState::opaque().apply(); // glClear respects the depth mask
+        unsafe {
+            gl::ClearColor(0.0, 0.0, 0.0, 1.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+        }
+
+        let mut context = FrameContext{
+            camera,
+            shader_manager: &mut self.shader_manager,
+            texture: &self.texture,
+            chunk_renderer: &mut self.chunk_renderer,
+            instanced_renderer: &self.instanced_renderer,
+            lighting: &self.lighting,
+            post_process: &self.post_process,
+            scene_texture: hdr_target.get_color_texture(),
+            output_framebuffer: output_framebuffer as GLuint,
+            output_viewport
+        };
+        let mut resolved = false;
+        for pass in self.passes.iter_mut() {
+            if pass.get_stage() >= RenderStage::PostProcess && !resolved {
+                if let Some(scene_target) = &self.scene_target {
+                    scene_target.resolve_into(hdr_target);
+                }
+                unsafe {
+                    gl::BindFramebuffer(gl::FRAMEBUFFER, output_framebuffer as GLuint);
+                    gl::Viewport(output_viewport[0], output_viewport[1], output_viewport[2], output_viewport[3]);
+                }
+                resolved = true;
+            }
+
+            pass.get_state().apply();
+            pass.draw(&mut context);
+        }
+    }
+}
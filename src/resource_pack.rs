@@ -0,0 +1,498 @@
+use crate::error::AssetError;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+pub const ASSETS_ENV: &str = "ABYSSAL_ECLIPSE_ASSETS";
+pub const PACK_MANIFEST: &str = "pack.txt";
+
+const ASSET_MARKER: &str = "shaders/common.glsl"; // a directory with this in it holds the builtin assets
+
+// Built with `--features embed-assets` the binary carries its own copy of the builtin assets,
+// used when no asset directory can be found. The list is generated by build.rs.
+#[cfg(feature = "embed-assets")]
+const EMBEDDED_ASSETS: &[(&str, &[u8])] = include!(concat!(env!("OUT_DIR"), "/embedded_assets.rs"));
+
+const ZIP_END_OF_CENTRAL_DIRECTORY: u32 = 0x06054b50;
+const ZIP_CENTRAL_DIRECTORY_ENTRY: u32 = 0x02014b50;
+const ZIP_LOCAL_HEADER: u32 = 0x04034b50;
+const ZIP_STORED: u16 = 0;
+const ZIP_DEFLATED: u16 = 8;
+
+// A directory or zip file laid out like the builtin assets (shaders/, textures/), with a manifest at its root:
+//   name My Pack
+//   description Sharper textures   (optional)
+// Assets are named by their path inside the pack, like "textures/water.png".
+#[derive(Clone)]
+pub struct ResourcePack {
+    name: String,
+    description: String,
+    source: PackSource
+}
+
+#[derive(Clone)]
+enum PackSource {
+    Directory(PathBuf),
+    #[cfg(feature = "embed-assets")]
+    Embedded(&'static [(&'static str, &'static [u8])]),
+    Zip { path: PathBuf, data: Vec<u8>, entries: HashMap<String, ZipEntry> } // read once, not watched for changes
+}
+
+#[derive(Clone)]
+struct ZipEntry {
+    method: u16,
+    header_offset: usize,
+    compressed_size: usize
+}
+
+impl ResourcePack {
+    // The assets that ship with the game, the bottom of every pack stack. No manifest needed.
+    // Taken from `directory` (--assets) or ASSETS_ENV when given, otherwise searched for next to
+    // the executable, in the source tree it was built from and in the working directory.
+    pub fn builtin(directory: Option<&Path>) -> Result<Self, AssetError> {
+        let builtin = |directory: PathBuf| ResourcePack{ name: "builtin".to_string(), description: directory.display().to_string(), source: PackSource::Directory(directory) };
+
+        if let Some(directory) = directory.map(Path::to_path_buf).or_else(|| env::var_os(ASSETS_ENV).map(PathBuf::from)) {
+            if !directory.join(ASSET_MARKER).is_file() {
+                return Err(AssetError::Pack{ path: directory.display().to_string(), message: format!("not an asset directory, {} is missing", ASSET_MARKER) });
+            }
+            return Ok(builtin(directory));
+        }
+
+        let mut candidates = vec![];
+        if let Some(executable_directory) = env::current_exe().ok().as_deref().and_then(Path::parent) {
+            candidates.push(executable_directory.join("assets"));     // release layout
+            candidates.push(executable_directory.join("../../src"));  // target/<profile>/ inside the repository
+        }
+        candidates.push(Path::new(env!("CARGO_MANIFEST_DIR")).join("src"));
+        candidates.push(PathBuf::from("./src"));
+        if let Some(directory) = candidates.into_iter().find(|directory| directory.join(ASSET_MARKER).is_file()) {
+            return Ok(builtin(directory));
+        }
+
+        #[cfg(feature = "embed-assets")]
+        return Ok(ResourcePack{ name: "builtin".to_string(), description: "embedded".to_string(), source: PackSource::Embedded(EMBEDDED_ASSETS) });
+        #[cfg(not(feature = "embed-assets"))]
+        Err(AssetError::Pack{ path: "builtin".to_string(), message: format!("assets not found, pass --assets <directory> or set {}", ASSETS_ENV) })
+    }
+
+    pub fn open(path: &Path) -> Result<Self, AssetError> {
+        let source = if path.is_dir() {
+            PackSource::Directory(path.to_path_buf())
+        } else {
+            let data = fs::read(path).map_err(|source| AssetError::Io{ path: path.display().to_string(), source })?;
+            let entries = read_zip_directory(&data).map_err(|message| AssetError::Pack{ path: path.display().to_string(), message })?;
+            PackSource::Zip{ path: path.to_path_buf(), data, entries }
+        };
+        let mut pack = ResourcePack{ name: String::new(), description: String::new(), source };
+
+        let manifest_path = pack.describe(PACK_MANIFEST);
+        let manifest = pack.read(PACK_MANIFEST)
+            .ok_or_else(|| AssetError::Pack{ path: path.display().to_string(), message: format!("missing {}", PACK_MANIFEST) })??;
+        for (number, line) in String::from_utf8_lossy(&manifest).lines().enumerate() {
+            let line = line.split('#').next().unwrap().trim();
+            match line.split_once(char::is_whitespace).map(|(key, value)| (key, value.trim())) {
+                Some(("name", value)) => pack.name = value.to_string(),
+                Some(("description", value)) => pack.description = value.to_string(),
+                Some((key, _)) => return Err(AssetError::Metadata{ path: manifest_path, line: number as u32 + 1, message: format!("Unknown setting: {}", key) }),
+                None if line.is_empty() => {},
+                None => return Err(AssetError::Metadata{ path: manifest_path, line: number as u32 + 1, message: format!("{} needs a value", line) })
+            }
+        }
+        if pack.name.is_empty() {
+            return Err(AssetError::Metadata{ path: manifest_path, line: 0, message: "the manifest needs a name".to_string() });
+        }
+        Ok(pack)
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_description(&self) -> &str {
+        &self.description
+    }
+
+    pub fn contains(&self, asset: &str) -> bool {
+        match &self.source {
+            PackSource::Directory(directory) => directory.join(asset).is_file(),
+            #[cfg(feature = "embed-assets")]
+            PackSource::Embedded(assets) => assets.iter().any(|(name, _)| *name == asset),
+            PackSource::Zip{ entries, .. } => entries.contains_key(asset)
+        }
+    }
+
+    // None when the pack doesn't have the asset.
+    pub fn read(&self, asset: &str) -> Option<Result<Vec<u8>, AssetError>> {
+        match &self.source {
+            PackSource::Directory(directory) => match fs::read(directory.join(asset)) {
+                Err(error) if error.kind() == io::ErrorKind::NotFound => None,
+                result => Some(result.map_err(|source| AssetError::Io{ path: self.describe(asset), source }))
+            },
+            #[cfg(feature = "embed-assets")]
+            PackSource::Embedded(assets) => assets.iter().find(|(name, _)| *name == asset).map(|(_, data)| Ok(data.to_vec())),
+            PackSource::Zip{ data, entries, .. } => {
+                let entry = entries.get(asset)?;
+                Some(read_zip_entry(data, entry).map_err(|message| AssetError::Pack{ path: self.describe(asset), message }))
+            }
+        }
+    }
+
+    // Only files in directory packs can change while the game runs.
+    pub fn get_modified(&self, asset: &str) -> Option<SystemTime> {
+        match &self.source {
+            PackSource::Directory(directory) => fs::metadata(directory.join(asset)).and_then(|metadata| metadata.modified()).ok(),
+            #[cfg(feature = "embed-assets")]
+            PackSource::Embedded(_) => None,
+            PackSource::Zip{ .. } => None
+        }
+    }
+
+    // Where an asset comes from, for messages.
+    pub fn describe(&self, asset: &str) -> String {
+        match &self.source {
+            PackSource::Directory(directory) => directory.join(asset).display().to_string(),
+            #[cfg(feature = "embed-assets")]
+            PackSource::Embedded(_) => format!("embedded:{}", asset),
+            PackSource::Zip{ path, .. } => format!("{}:{}", path.display(), asset)
+        }
+    }
+}
+
+// Looks assets up through a stack of packs, later packs override earlier ones.
+pub struct AssetResolver {
+    packs: Vec<ResourcePack> // the builtin pack first
+}
+
+impl AssetResolver {
+    pub fn new(builtin: ResourcePack, pack_paths: &[PathBuf]) -> Result<Self, AssetError> {
+        let mut packs = vec![builtin];
+        for path in pack_paths {
+            packs.push(ResourcePack::open(path)?);
+        }
+        Ok(AssetResolver{ packs })
+    }
+
+    pub fn get_builtin(&self) -> &ResourcePack {
+        &self.packs[0]
+    }
+
+    pub fn get_packs(&self) -> &[ResourcePack] {
+        &self.packs
+    }
+
+    // The pack an asset is loaded from.
+    pub fn find(&self, asset: &str) -> Option<&ResourcePack> {
+        self.packs.iter().rev().find(|pack| pack.contains(asset))
+    }
+
+    pub fn read(&self, asset: &str) -> Result<Vec<u8>, AssetError> {
+        match self.find(asset) {
+            Some(pack) => pack.read(asset).unwrap_or_else(|| Err(not_found(asset))),
+            None => Err(not_found(asset))
+        }
+    }
+
+    pub fn read_to_string(&self, asset: &str) -> Result<String, AssetError> {
+        String::from_utf8(self.read(asset)?).map_err(|_| AssetError::Io{ path: asset.to_string(), source: io::Error::new(io::ErrorKind::InvalidData, "not valid UTF-8") })
+    }
+
+    // Changes when the file changes, or when a different pack starts providing it.
+    pub fn get_version(&self, asset: &str) -> Option<(usize, Option<SystemTime>)> {
+        let index = self.packs.iter().rposition(|pack| pack.contains(asset))?;
+        Some((index, self.packs[index].get_modified(asset)))
+    }
+}
+
+fn not_found(asset: &str) -> AssetError {
+    AssetError::Io{ path: asset.to_string(), source: io::Error::new(io::ErrorKind::NotFound, "not found in any resource pack") }
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16, String> {
+    data.get(offset..offset + 2).map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]])).ok_or("truncated zip file".to_string())
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32, String> {
+    data.get(offset..offset + 4).map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])).ok_or("truncated zip file".to_string())
+}
+
+// Reads the central directory at the end of the file. Zip64 and encrypted archives aren't supported.
+fn read_zip_directory(data: &[u8]) -> Result<HashMap<String, ZipEntry>, String> {
+    // The end record is 22 bytes followed by a comment of up to 64 KiB.
+    let end = (0..data.len().saturating_sub(21)).rev().take(22 + u16::MAX as usize)
+        .find(|offset| read_u32(data, *offset) == Ok(ZIP_END_OF_CENTRAL_DIRECTORY))
+        .ok_or("not a zip file")?;
+    let entry_count = read_u16(data, end + 10)? as usize;
+    let mut offset = read_u32(data, end + 16)? as usize;
+
+    let mut entries = HashMap::new();
+    for _ in 0..entry_count {
+        if read_u32(data, offset)? != ZIP_CENTRAL_DIRECTORY_ENTRY {
+            return Err("corrupt central directory".to_string());
+        }
+        let flags = read_u16(data, offset + 8)?;
+        let method = read_u16(data, offset + 10)?;
+        let compressed_size = read_u32(data, offset + 20)? as usize;
+        let name_length = read_u16(data, offset + 28)? as usize;
+        let extra_length = read_u16(data, offset + 30)? as usize;
+        let comment_length = read_u16(data, offset + 32)? as usize;
+        let header_offset = read_u32(data, offset + 42)? as usize;
+        let name = data.get(offset + 46..offset + 46 + name_length).ok_or("truncated zip file")?;
+        let name = String::from_utf8_lossy(name).replace('\\', "/");
+
+        if flags & 1 != 0 {
+            return Err(format!("{} is encrypted", name));
+        }
+        if !name.ends_with('/') {
+            entries.insert(name, ZipEntry{ method, header_offset, compressed_size });
+        }
+        offset += 46 + name_length + extra_length + comment_length;
+    }
+    Ok(entries)
+}
+
+fn read_zip_entry(data: &[u8], entry: &ZipEntry) -> Result<Vec<u8>, String> {
+    if read_u32(data, entry.header_offset)? != ZIP_LOCAL_HEADER {
+        return Err("corrupt local header".to_string());
+    }
+    let name_length = read_u16(data, entry.header_offset + 26)? as usize;
+    let extra_length = read_u16(data, entry.header_offset + 28)? as usize;
+    let start = entry.header_offset + 30 + name_length + extra_length;
+    let compressed = data.get(start..start + entry.compressed_size).ok_or("truncated zip file")?;
+
+    match entry.method {
+        ZIP_STORED => Ok(compressed.to_vec()),
+        ZIP_DEFLATED => miniz_oxide::inflate::decompress_to_vec(compressed).map_err(|error| format!("failed to inflate: {:?}", error.status)),
+        method => Err(format!("unsupported compression method {}", method))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A directory under the system temp directory, removed again when dropped.
+    struct TempDirectory(PathBuf);
+
+    impl TempDirectory {
+        fn new(name: &str) -> Self {
+            let path = env::temp_dir().join(format!("abyssal-eclipse-{}-{}", std::process::id(), name));
+            let _ = fs::remove_dir_all(&path);
+            fs::create_dir_all(&path).unwrap();
+            TempDirectory(path)
+        }
+
+        fn write(&self, asset: &str, data: &[u8]) -> PathBuf {
+            let path = self.0.join(asset);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, data).unwrap();
+            path
+        }
+    }
+
+    impl Drop for TempDirectory {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    struct TestEntry<'a> {
+        name: &'a str,
+        data: &'a [u8],
+        method: u16,
+        flags: u16
+    }
+
+    fn entry<'a>(name: &'a str, data: &'a [u8], method: u16) -> TestEntry<'a> {
+        TestEntry{ name, data, method, flags: 0 }
+    }
+
+    // Local headers and data, then the central directory and the end record, like any zip tool writes them.
+    fn build_zip(entries: &[TestEntry]) -> Vec<u8> {
+        let mut data = vec![];
+        let mut directory = vec![];
+        for entry in entries {
+            let compressed = match entry.method {
+                ZIP_DEFLATED => miniz_oxide::deflate::compress_to_vec(entry.data, 6),
+                _ => entry.data.to_vec()
+            };
+            let header_offset = data.len() as u32;
+            data.extend_from_slice(&ZIP_LOCAL_HEADER.to_le_bytes());
+            data.extend_from_slice(&[20, 0]);
+            data.extend_from_slice(&entry.flags.to_le_bytes());
+            data.extend_from_slice(&entry.method.to_le_bytes());
+            data.extend_from_slice(&[0; 8]); // time, date, crc
+            data.extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+            data.extend_from_slice(&(entry.data.len() as u32).to_le_bytes());
+            data.extend_from_slice(&(entry.name.len() as u16).to_le_bytes());
+            data.extend_from_slice(&[0, 0]);
+            data.extend_from_slice(entry.name.as_bytes());
+            data.extend_from_slice(&compressed);
+
+            directory.extend_from_slice(&ZIP_CENTRAL_DIRECTORY_ENTRY.to_le_bytes());
+            directory.extend_from_slice(&[20, 0, 20, 0]);
+            directory.extend_from_slice(&entry.flags.to_le_bytes());
+            directory.extend_from_slice(&entry.method.to_le_bytes());
+            directory.extend_from_slice(&[0; 8]);
+            directory.extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+            directory.extend_from_slice(&(entry.data.len() as u32).to_le_bytes());
+            directory.extend_from_slice(&(entry.name.len() as u16).to_le_bytes());
+            directory.extend_from_slice(&[0; 12]); // extra, comment, disk, attributes
+            directory.extend_from_slice(&header_offset.to_le_bytes());
+            directory.extend_from_slice(entry.name.as_bytes());
+        }
+
+        let directory_offset = data.len() as u32;
+        data.extend_from_slice(&directory);
+        data.extend_from_slice(&ZIP_END_OF_CENTRAL_DIRECTORY.to_le_bytes());
+        data.extend_from_slice(&[0; 4]);
+        data.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+        data.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+        data.extend_from_slice(&(directory.len() as u32).to_le_bytes());
+        data.extend_from_slice(&directory_offset.to_le_bytes());
+        data.extend_from_slice(&[0, 0]);
+        data
+    }
+
+    fn read_all(data: &[u8]) -> Result<HashMap<String, Vec<u8>>, String> {
+        read_zip_directory(data)?.into_iter().map(|(name, entry)| Ok((name, read_zip_entry(data, &entry)?))).collect()
+    }
+
+    #[test]
+    fn reads_stored_and_deflated_entries() {
+        let text = b"#version 330 core\n".repeat(20);
+        let zip = build_zip(&[entry("pack.txt", b"name Test", ZIP_STORED), entry("shaders\\fog.glsl", &text, ZIP_DEFLATED), entry("textures/", b"", ZIP_STORED)]);
+        let files = read_all(&zip).unwrap();
+        assert_eq!(files.len(), 2); // the directory entry is skipped
+        assert_eq!(files["pack.txt"], b"name Test");
+        assert_eq!(files["shaders/fog.glsl"], text);
+    }
+
+    #[test]
+    fn rejects_truncated_archives() {
+        let zip = build_zip(&[entry("pack.txt", b"name Test", ZIP_STORED), entry("a.glsl", &[7; 100], ZIP_DEFLATED)]);
+        for length in 0..zip.len() {
+            assert!(read_all(&zip[..length]).is_err(), "{} of {} bytes", length, zip.len());
+        }
+    }
+
+    #[test]
+    fn rejects_entries_past_the_end() {
+        let zip = build_zip(&[entry("a.glsl", b"void main() {}", ZIP_STORED)]);
+        let mut entry = read_zip_directory(&zip).unwrap().remove("a.glsl").unwrap();
+        entry.compressed_size = zip.len();
+        assert!(read_zip_entry(&zip, &entry).is_err());
+        entry.header_offset = zip.len() - 2;
+        assert!(read_zip_entry(&zip, &entry).is_err());
+    }
+
+    #[test]
+    fn rejects_a_bad_central_directory() {
+        let zip = build_zip(&[entry("a.glsl", b"void main() {}", ZIP_STORED)]);
+        let end = zip.len() - 22;
+        let directory_offset = read_u32(&zip, end + 16).unwrap() as usize;
+
+        let mut bad_signature = zip.clone();
+        bad_signature[directory_offset] ^= 0xFF;
+        assert_eq!(read_zip_directory(&bad_signature).err().as_deref(), Some("corrupt central directory"));
+
+        let mut bad_offset = zip.clone();
+        bad_offset[end + 16..end + 20].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(read_zip_directory(&bad_offset).is_err());
+
+        let mut extra_entries = zip.clone();
+        extra_entries[end + 10] = 2;
+        assert!(read_zip_directory(&extra_entries).is_err());
+
+        assert_eq!(read_zip_directory(b"not a zip at all, just text").err().as_deref(), Some("not a zip file"));
+    }
+
+    #[test]
+    fn survives_corrupted_bytes() {
+        let zip = build_zip(&[entry("pack.txt", b"name Test", ZIP_STORED), entry("a.glsl", &[7; 100], ZIP_DEFLATED)]);
+        for index in 0..zip.len() {
+            let mut corrupted = zip.clone();
+            corrupted[index] ^= 0xFF;
+            let _ = read_all(&corrupted); // errors are fine, panics aren't
+        }
+    }
+
+    #[test]
+    fn rejects_encrypted_and_unknown_entries() {
+        let encrypted = build_zip(&[TestEntry{ name: "a.glsl", data: b"secret", method: ZIP_STORED, flags: 1 }]);
+        assert_eq!(read_zip_directory(&encrypted).err().as_deref(), Some("a.glsl is encrypted"));
+
+        let bzip2 = build_zip(&[entry("a.glsl", b"data", 12)]);
+        assert!(read_all(&bzip2).unwrap_err().contains("unsupported compression method 12"));
+
+        let mut bad_deflate = build_zip(&[entry("a.glsl", &[7; 100], ZIP_DEFLATED)]);
+        bad_deflate[30 + "a.glsl".len()] = 0xFF; // invalid block type
+        assert!(read_all(&bad_deflate).unwrap_err().starts_with("failed to inflate"));
+    }
+
+    #[test]
+    fn opens_directory_and_zip_packs() {
+        let directory = TempDirectory::new("open");
+        directory.write("folder/pack.txt", b"# comment\nname Folder Pack\ndescription  Sharper textures \n");
+        let pack = ResourcePack::open(&directory.0.join("folder")).unwrap();
+        assert_eq!((pack.get_name(), pack.get_description()), ("Folder Pack", "Sharper textures"));
+
+        let zip_path = directory.write("zipped.zip", &build_zip(&[entry("pack.txt", b"name Zipped", ZIP_DEFLATED), entry("textures/a.png", b"png", ZIP_STORED)]));
+        let pack = ResourcePack::open(&zip_path).unwrap();
+        assert_eq!(pack.get_name(), "Zipped");
+        assert!(pack.contains("textures/a.png") && !pack.contains("textures/b.png"));
+        assert_eq!(pack.read("textures/a.png").unwrap().unwrap(), b"png");
+        assert!(pack.read("textures/b.png").is_none());
+    }
+
+    #[test]
+    fn rejects_bad_manifests() {
+        let directory = TempDirectory::new("manifests");
+        directory.write("empty/textures/a.png", b"png");
+        directory.write("unnamed/pack.txt", b"description No name\n");
+        directory.write("unknown/pack.txt", b"name Pack\nversion 2\n");
+        assert!(matches!(ResourcePack::open(&directory.0.join("empty")), Err(AssetError::Pack{ .. })));
+        assert!(matches!(ResourcePack::open(&directory.0.join("unnamed")), Err(AssetError::Metadata{ line: 0, .. })));
+        assert!(matches!(ResourcePack::open(&directory.0.join("unknown")), Err(AssetError::Metadata{ line: 2, .. })));
+        assert!(ResourcePack::open(&directory.write("broken.zip", b"PK but not really")).is_err());
+    }
+
+    #[test]
+    fn later_packs_override_earlier_ones() {
+        let directory = TempDirectory::new("resolver");
+        directory.write("builtin/shaders/common.glsl", b"builtin common");
+        directory.write("builtin/textures/a.png", b"builtin a");
+        directory.write("builtin/textures/b.png", b"builtin b");
+        directory.write("first/pack.txt", b"name First");
+        directory.write("first/textures/a.png", b"first a");
+        directory.write("first/textures/b.png", b"first b");
+        let second = directory.write("second.zip", &build_zip(&[entry("pack.txt", b"name Second", ZIP_STORED), entry("textures/b.png", b"second b", ZIP_DEFLATED)]));
+
+        let builtin = ResourcePack::builtin(Some(&directory.0.join("builtin"))).unwrap();
+        let assets = AssetResolver::new(builtin, &[directory.0.join("first"), second]).unwrap();
+        let names: Vec<&str> = assets.get_packs().iter().map(ResourcePack::get_name).collect();
+        assert_eq!(names, vec!["builtin", "First", "Second"]);
+
+        assert_eq!(assets.find("shaders/common.glsl").map(ResourcePack::get_name), Some("builtin"));
+        assert_eq!(assets.find("textures/a.png").map(ResourcePack::get_name), Some("First"));
+        assert_eq!(assets.find("textures/b.png").map(ResourcePack::get_name), Some("Second"));
+        assert!(assets.find("textures/c.png").is_none());
+
+        assert_eq!(assets.read("textures/a.png").unwrap(), b"first a");
+        assert_eq!(assets.read("textures/b.png").unwrap(), b"second b");
+        assert_eq!(assets.read_to_string("shaders/common.glsl").unwrap(), "builtin common");
+        assert!(assets.read("textures/c.png").is_err());
+        assert_eq!(assets.get_version("textures/b.png"), Some((2, None))); // zips aren't watched
+        assert_eq!(assets.get_version("textures/a.png").map(|(index, _)| index), Some(1));
+    }
+
+    #[test]
+    fn builtin_directory_needs_the_marker() {
+        let directory = TempDirectory::new("builtin");
+        directory.write("textures/a.png", b"png");
+        assert!(matches!(ResourcePack::builtin(Some(&directory.0)), Err(AssetError::Pack{ .. })));
+    }
+}
@@ -0,0 +1,45 @@
+use crate::framebuffer::Framebuffer;
+use gl::types::GLsizei;
+use image::{imageops, RgbaImage};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const SCREENSHOT_DIRECTORY: &str = "./screenshots";
+
+// Reads back the currently bound framebuffer (the default one unless an FBO is bound).
+pub fn read_pixels(width: u32, height: u32) -> RgbaImage {
+    let mut pixels: Vec<u8> = vec![0; (width * height * 4) as usize];
+    unsafe {
+        gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+        gl::ReadPixels(0, 0, width as GLsizei, height as GLsizei, gl::RGBA, gl::UNSIGNED_BYTE, pixels.as_mut_ptr() as *mut _);
+    }
+
+    let image = RgbaImage::from_raw(width, height, pixels).expect("Pixel buffer size mismatch");
+    imageops::flip_vertical(&image) // GL rows start at the bottom
+}
+
+// Renders a frame into an offscreen target `scale` times the given size and reads it back.
+pub fn capture_scaled<F: FnOnce()>(width: u32, height: u32, scale: u32, render: F) -> Result<RgbaImage, &'static str> {
+    let target = Framebuffer::generate(width * scale, height * scale)?;
+    target.bind();
+    render();
+    let image = read_pixels(target.get_width(), target.get_height());
+    target.unbind();
+
+    unsafe { gl::Viewport(0, 0, width as GLsizei, height as GLsizei); }
+
+    Ok(image)
+}
+
+// Writes the image as screenshot_<unix seconds>_<millis>.png into the given directory.
+pub fn save(image: &RgbaImage, directory: &str) -> Result<PathBuf, String> {
+    fs::create_dir_all(directory).map_err(|error| format!("Failed to create {}: {}", directory, error))?;
+
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    let file_name = format!("screenshot_{}_{:03}.png", timestamp.as_secs(), timestamp.subsec_millis());
+    let path = Path::new(directory).join(file_name);
+
+    image.save(&path).map_err(|error| format!("Failed to save {}: {}", path.display(), error))?;
+    Ok(path)
+}
@@ -0,0 +1,107 @@
+use crate::object::{create_error_program, create_program, Program};
+use crate::resource_pack::AssetResolver;
+use std::rc::Rc;
+use std::time::{Duration, Instant, SystemTime};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Clone, Copy)]
+pub struct ProgramHandle(usize);
+
+struct ProgramEntry {
+    vertex_path: String,
+    fragment_path: String,
+    program: Program,
+    versions: Vec<Option<(usize, Option<SystemTime>)>>
+}
+
+// Owns every shader program and recompiles them when one of their source files
+// (includes too) changes on disk, moves to another resource pack or the injected defines change. A program is only
+// replaced when the new version compiles and links, otherwise the error is logged
+// and the old program keeps running.
+pub struct ShaderManager {
+    assets: Rc<AssetResolver>,
+    programs: Vec<ProgramEntry>,
+    defines: Vec<(String, String)>,
+    last_poll: Instant
+}
+
+impl ShaderManager {
+    pub fn new(assets: Rc<AssetResolver>) -> Self {
+        ShaderManager{ assets, programs: vec![], defines: vec![], last_poll: Instant::now() }
+    }
+
+    // Rebuilds every program from the new pack stack.
+    pub fn set_assets(&mut self, assets: Rc<AssetResolver>) {
+        self.assets = assets;
+        for entry in &mut self.programs {
+            reload(entry, &self.assets, &self.defines);
+        }
+    }
+
+    // Logs the error and uses the magenta error program instead. The shader files stay
+    // watched, so fixing them swaps the real program in.
+    pub fn load_or_fallback(&mut self, vertex_path: &str, fragment_path: &str) -> ProgramHandle {
+        let program = create_program(&self.assets, vertex_path, fragment_path, &self.defines).unwrap_or_else(|error| {
+            eprintln!("{}", error);
+            create_error_program()
+        });
+
+        let mut entry = ProgramEntry{ vertex_path: vertex_path.to_string(), fragment_path: fragment_path.to_string(), program, versions: vec![] };
+        entry.versions = source_versions(&entry, &self.assets);
+
+        self.programs.push(entry);
+        ProgramHandle(self.programs.len() - 1)
+    }
+
+    pub fn get(&mut self, handle: ProgramHandle) -> &mut Program {
+        &mut self.programs[handle.0].program
+    }
+
+    // Injected as `#define name value` after #version in every shader, loaded programs are rebuilt.
+    pub fn set_define(&mut self, name: &str, value: &str) {
+        match self.defines.iter_mut().find(|(define, _)| define == name) {
+            Some((_, current)) if current == value => return,
+            Some((_, current)) => *current = value.to_string(),
+            None => self.defines.push((name.to_string(), value.to_string()))
+        }
+
+        for entry in &mut self.programs {
+            reload(entry, &self.assets, &self.defines);
+        }
+    }
+
+    // Cheap enough to call every frame, the files are only checked every POLL_INTERVAL.
+    pub fn reload_changed(&mut self) {
+        if self.last_poll.elapsed() < POLL_INTERVAL {
+            return;
+        }
+        self.last_poll = Instant::now();
+
+        for entry in &mut self.programs {
+            if source_versions(entry, &self.assets) != entry.versions {
+                reload(entry, &self.assets, &self.defines);
+            }
+        }
+    }
+}
+
+fn reload(entry: &mut ProgramEntry, assets: &AssetResolver, defines: &[(String, String)]) {
+    match create_program(assets, &entry.vertex_path, &entry.fragment_path, defines) {
+        Ok(program) => {
+            entry.program = program; // uniforms are reflected again while linking
+            println!("Reloaded shader program {} + {}", entry.vertex_path, entry.fragment_path);
+        },
+        Err(error) => eprintln!("Shader reload failed, keeping the previous program:\n{}", error)
+    }
+    // On failure too, a broken file shouldn't be retried every poll, only on its next change.
+    entry.versions = source_versions(entry, assets);
+}
+
+fn source_versions(entry: &ProgramEntry, assets: &AssetResolver) -> Vec<Option<(usize, Option<SystemTime>)>> {
+    let files = match entry.program.source_files() {
+        [] => vec![entry.vertex_path.clone(), entry.fragment_path.clone()], // error program
+        files => files.to_vec()
+    };
+    files.iter().map(|path| assets.get_version(path)).collect()
+}
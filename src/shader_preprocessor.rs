@@ -0,0 +1,95 @@
+use crate::error::AssetError;
+use crate::resource_pack::AssetResolver;
+
+// GLSL source after `#include "file"` expansion and `#define` injection, with every
+// output line remembering which file and line it came from so compiler errors can
+// point at the original source.
+pub struct PreprocessedSource {
+    pub code: String,
+    pub files: Vec<String>,
+    line_origins: Vec<Option<(usize, u32)>> // (index into files, line), None for injected defines
+}
+
+impl PreprocessedSource {
+    // `line` is 1-based, as reported by the GLSL compiler.
+    pub fn origin(&self, line: u32) -> Option<(&str, u32)> {
+        let (file_index, original_line) = (*self.line_origins.get((line as usize).checked_sub(1)?)?)?;
+        Some((&self.files[file_index], original_line))
+    }
+}
+
+// `path` is an asset path, includes are resolved relative to it through the same resource packs.
+pub fn preprocess(assets: &AssetResolver, path: &str, defines: &[(String, String)]) -> Result<PreprocessedSource, AssetError> {
+    let mut source = PreprocessedSource{ code: String::new(), files: vec![], line_origins: vec![] };
+    expand(&mut source, assets, path, defines, &mut vec![], true)?;
+    Ok(source)
+}
+
+fn expand(source: &mut PreprocessedSource, assets: &AssetResolver, path: &str, defines: &[(String, String)], stack: &mut Vec<String>, root: bool) -> Result<(), AssetError> {
+    let text = assets.read_to_string(path)?;
+
+    source.files.push(path.to_string());
+    let file_index = source.files.len() - 1;
+    stack.push(path.to_string());
+
+    // Defines go right after #version, or at the very top when there is none.
+    let mut defines_pending = root;
+    if root && !text.lines().any(|line| line.trim_start().starts_with("#version")) {
+        inject_defines(source, defines);
+        defines_pending = false;
+    }
+
+    for (index, line) in text.lines().enumerate() {
+        let line_number = index as u32 + 1;
+        let trimmed = line.trim_start();
+
+        if trimmed.starts_with("#version") {
+            if root {
+                push_line(source, line, Some((file_index, line_number)));
+            }
+            if defines_pending {
+                inject_defines(source, defines);
+                defines_pending = false;
+            }
+            continue;
+        }
+
+        if let Some(rest) = trimmed.strip_prefix("#include") {
+            let include_error = |message: String| AssetError::Include{ path: path.to_string(), line: line_number, message };
+
+            let name = rest.trim().strip_prefix('"').and_then(|name| name.strip_suffix('"'))
+                .ok_or_else(|| include_error("malformed #include, expected #include \"file\"".to_string()))?;
+            let include_path = match path.rsplit_once('/') {
+                Some((directory, _)) => format!("{}/{}", directory, name),
+                None => name.to_string()
+            };
+
+            if stack.contains(&include_path) {
+                return Err(include_error(format!("recursive #include of {}", include_path)));
+            }
+
+            expand(source, assets, &include_path, defines, stack, false).map_err(|error| match error {
+                AssetError::Io{ path: missing, source } if missing == include_path => include_error(format!("cannot open {}: {}", missing, source)),
+                error => error
+            })?;
+            continue;
+        }
+
+        push_line(source, line, Some((file_index, line_number)));
+    }
+
+    stack.pop();
+    Ok(())
+}
+
+fn inject_defines(source: &mut PreprocessedSource, defines: &[(String, String)]) {
+    for (name, value) in defines {
+        push_line(source, &format!("#define {} {}", name, value), None);
+    }
+}
+
+fn push_line(source: &mut PreprocessedSource, line: &str, origin: Option<(usize, u32)>) {
+    source.code.push_str(line);
+    source.code.push('\n');
+    source.line_origins.push(origin);
+}
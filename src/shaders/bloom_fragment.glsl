@@ -0,0 +1,15 @@
+#version 330 core
+
+in vec2 uv;
+out vec4 Color;
+
+uniform sampler2D u_scene;
+uniform float u_threshold;
+
+// Keeps only what is brighter than the threshold, with a soft knee so bloom doesn't pop in.
+void main() {
+    vec3 color = texture(u_scene, uv).rgb;
+    float brightness = max(color.r, max(color.g, color.b));
+    float contribution = clamp((brightness - u_threshold) / max(brightness, 0.0001), 0.0, 1.0);
+    Color = vec4(color * contribution, 1.0);
+}
@@ -0,0 +1,19 @@
+#version 330 core
+
+in vec2 uv;
+out vec4 Color;
+
+uniform sampler2D u_image;
+uniform vec2 u_direction; // one texel along the blur axis
+
+const float WEIGHTS[5] = float[](0.227027, 0.1945946, 0.1216216, 0.054054, 0.016216);
+
+// One axis of a separable 9 tap gaussian.
+void main() {
+    vec3 color = texture(u_image, uv).rgb * WEIGHTS[0];
+    for (int i = 1; i < 5; i++) {
+        color += texture(u_image, uv + u_direction * i).rgb * WEIGHTS[i];
+        color += texture(u_image, uv - u_direction * i).rgb * WEIGHTS[i];
+    }
+    Color = vec4(color, 1.0);
+}
@@ -0,0 +1,8 @@
+// Uploaded once per frame by the renderer, shared by every program.
+layout (std140) uniform Camera {
+    mat4 u_matrix_projection;
+    mat4 u_matrix_camera;
+    vec4 u_fog; // x start distance, y end distance, z mode: 0 off, 1 linear, 2 exponential
+};
+
+uniform mat4 u_matrix_transform;
@@ -0,0 +1,38 @@
+#version 330 core
+
+in vec2 uv;
+out vec4 Color;
+
+uniform sampler2D u_scene;
+uniform sampler2D u_bloom;
+uniform float u_bloom_intensity;
+uniform float u_exposure;
+uniform float u_gamma;   // only without u_srgb
+uniform int u_srgb;      // encode the linear scene for an sRGB display
+uniform int u_tonemapping; // 0 none, 1 Reinhard, 2 ACES, matches Tonemapping
+
+// Narkowicz's fit of the ACES filmic curve.
+vec3 aces(vec3 color) {
+    return clamp((color * (2.51 * color + 0.03)) / (color * (2.43 * color + 0.59) + 0.14), 0.0, 1.0);
+}
+
+vec3 linear_to_srgb(vec3 color) {
+    return mix(color * 12.92, 1.055 * pow(color, vec3(1.0 / 2.4)) - 0.055, step(0.0031308, color));
+}
+
+void main() {
+    vec3 color = texture(u_scene, uv).rgb + texture(u_bloom, uv).rgb * u_bloom_intensity;
+    color *= u_exposure;
+
+    if (u_tonemapping == 1)
+        color = color / (color + 1.0);
+    else if (u_tonemapping == 2)
+        color = aces(color);
+    else
+        color = clamp(color, 0.0, 1.0);
+
+    if (u_srgb == 1)
+        Color = vec4(linear_to_srgb(color), 1.0);
+    else
+        Color = vec4(pow(color, vec3(1.0 / u_gamma)), 1.0);
+}
@@ -0,0 +1,13 @@
+// Needs common.glsl (u_fog) and lighting.glsl (u_sky_horizon).
+
+// Blends towards the sky's horizon colour, fully fogged by the end distance so nothing is
+// visible where the far plane cuts chunks off.
+vec3 apply_fog(vec3 color, float distance)
+{
+    float amount = clamp((distance - u_fog.x) / (u_fog.y - u_fog.x), 0.0, 1.0);
+    if (u_fog.z == 0.0)
+        return color;
+    if (u_fog.z == 2.0)
+        amount = 1.0 - exp(-9.0 * amount * amount); // squared exponential, 0.9999 at the end distance
+    return mix(color, u_sky_horizon.rgb, amount);
+}
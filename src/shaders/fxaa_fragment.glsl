@@ -0,0 +1,41 @@
+#version 330 core
+
+in vec2 uv;
+out vec4 Color;
+
+uniform sampler2D u_image; // tonemapped
+uniform vec2 u_texel;
+
+const float SPAN_MAX = 8.0;
+const float REDUCE_MIN = 1.0 / 128.0;
+const float REDUCE_MUL = 1.0 / 8.0;
+
+float luma(vec3 color) {
+    return dot(color, vec3(0.299, 0.587, 0.114));
+}
+
+// The classic console FXAA: blur along the edge found from the luma of the 4 diagonal neighbours.
+void main() {
+    float luma_nw = luma(texture(u_image, uv + vec2(-1.0, -1.0) * u_texel).rgb);
+    float luma_ne = luma(texture(u_image, uv + vec2(1.0, -1.0) * u_texel).rgb);
+    float luma_sw = luma(texture(u_image, uv + vec2(-1.0, 1.0) * u_texel).rgb);
+    float luma_se = luma(texture(u_image, uv + vec2(1.0, 1.0) * u_texel).rgb);
+    vec3 center = texture(u_image, uv).rgb;
+    float luma_m = luma(center);
+
+    float luma_min = min(luma_m, min(min(luma_nw, luma_ne), min(luma_sw, luma_se)));
+    float luma_max = max(luma_m, max(max(luma_nw, luma_ne), max(luma_sw, luma_se)));
+
+    vec2 direction = vec2(-((luma_nw + luma_ne) - (luma_sw + luma_se)), (luma_nw + luma_sw) - (luma_ne + luma_se));
+    float reduce = max((luma_nw + luma_ne + luma_sw + luma_se) * 0.25 * REDUCE_MUL, REDUCE_MIN);
+    float scale = 1.0 / (min(abs(direction.x), abs(direction.y)) + reduce);
+    direction = clamp(direction * scale, vec2(-SPAN_MAX), vec2(SPAN_MAX)) * u_texel;
+
+    vec3 near = 0.5 * (texture(u_image, uv + direction * (1.0 / 3.0 - 0.5)).rgb
+                     + texture(u_image, uv + direction * (2.0 / 3.0 - 0.5)).rgb);
+    vec3 far = near * 0.5 + 0.25 * (texture(u_image, uv - direction * 0.5).rgb
+                                   + texture(u_image, uv + direction * 0.5).rgb);
+
+    float luma_far = luma(far);
+    Color = vec4((luma_far < luma_min || luma_far > luma_max) ? near : far, 1.0);
+}
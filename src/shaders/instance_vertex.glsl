@@ -0,0 +1,32 @@
+#version 330 core
+layout (location = 0) in vec3 Position;
+layout (location = 1) in vec2 inTextureCoordinate;
+layout (location = 2) in mat4 inInstanceTransform; // locations 2-5
+layout (location = 6) in float inTextureLayer;
+
+out vec3 textureCoordinate;
+out vec3 normal;
+out float ambientOcclusion;
+out vec3 light;
+out vec3 worldPosition;
+out float viewDepth;
+out float viewDistance;
+
+#include "common.glsl"
+
+// The ShapeData cube has four vertices per face, in this face order.
+const vec3 FACE_NORMALS[6] = vec3[6](vec3(-1.0, 0.0, 0.0), vec3(0.0, 0.0, 1.0), vec3(1.0, 0.0, 0.0), vec3(0.0, 0.0, -1.0), vec3(0.0, 1.0, 0.0), vec3(0.0, -1.0, 0.0));
+
+
+void main()
+{
+    textureCoordinate = vec3(inTextureCoordinate, inTextureLayer);
+    normal = mat3(inInstanceTransform) * FACE_NORMALS[gl_VertexID / 4];
+    ambientOcclusion = 1.0;
+    light = vec3(0.0, 1.0, 0.0); // open sky, no block light
+    worldPosition = (inInstanceTransform * vec4(Position, 1.0)).xyz;
+    vec4 viewPosition = u_matrix_camera * vec4(worldPosition, 1.0);
+    viewDepth = -viewPosition.z;
+    viewDistance = length(viewPosition.xyz);
+    gl_Position = u_matrix_projection * viewPosition;
+}
@@ -0,0 +1,70 @@
+// Uploaded once per frame by the renderer, see lighting.rs.
+layout (std140) uniform Lighting {
+    vec4 u_sun_direction; // xyz towards the sun by day, the moon at night
+    vec4 u_sun_color;
+    vec4 u_ambient_color;
+    vec4 u_sky_zenith;    // rgb, w = daylight
+    vec4 u_sky_horizon;   // rgb, w = star visibility
+    vec4 u_sun_position;  // xyz towards the sun, also below the horizon
+};
+
+// Filled by the shadow pass, see shadow_map.rs. SHADOW_CASCADES is defined by the renderer.
+layout (std140) uniform Shadows {
+    mat4 u_shadow_matrices[SHADOW_CASCADES];
+    vec4 u_cascade_splits; // view space distance where each cascade ends
+};
+
+uniform sampler2DArrayShadow u_shadow_map;
+
+const float SHADOW_NORMAL_OFFSET = 0.04;
+const float SHADOW_DEPTH_BIAS = 0.0005;
+
+// 1 in sunlight, 0 in shadow. Averages a 3x3 grid of hardware filtered compares (PCF).
+float sun_shadow(vec3 world_position, vec3 normal, float view_depth)
+{
+    int cascade = 0;
+    while (cascade < SHADOW_CASCADES && view_depth > u_cascade_splits[cascade])
+        cascade++;
+    if (cascade == SHADOW_CASCADES)
+        return 1.0;
+
+    // Pushing the sample point off the surface avoids shadow acne on faces at grazing angles.
+    vec4 position = u_shadow_matrices[cascade] * vec4(world_position + normal * SHADOW_NORMAL_OFFSET, 1.0);
+    vec3 coordinate = position.xyz / position.w * 0.5 + 0.5;
+    if (coordinate.z > 1.0)
+        return 1.0;
+
+    vec2 texel = 1.0 / vec2(textureSize(u_shadow_map, 0).xy);
+    float lit = 0.0;
+    for (int x = -1; x <= 1; x++)
+        for (int y = -1; y <= 1; y++)
+            lit += texture(u_shadow_map, vec4(coordinate.xy + vec2(x, y) * texel, cascade, coordinate.z - SHADOW_DEPTH_BIAS));
+    return lit / 9.0;
+}
+
+// Fixed per-axis factors so faces the sun doesn't reach still read as different sides of a block.
+float face_shade(vec3 normal)
+{
+    return dot(abs(normal), vec3(0.6, normal.y > 0.0 ? 1.0 : 0.5, 0.8));
+}
+
+// Ambient + Lambert diffuse, multiplied onto the albedo.
+vec3 sun_light(vec3 normal, float shadow)
+{
+    float diffuse = max(dot(normal, u_sun_direction.xyz), 0.0) * shadow;
+    return (u_ambient_color.rgb + u_sun_color.rgb * diffuse) * face_shade(normal);
+}
+
+const vec3 BLOCK_LIGHT_COLOR = vec3(1.0, 0.85, 0.6);
+
+// Each level is 80% as bright as the one above it, level 0 is close to black.
+float light_curve(float level)
+{
+    return pow(0.8, 15.0 * (1.0 - level));
+}
+
+// Sunlight scaled by how much skylight reaches the block, brightened by nearby light sources.
+vec3 voxel_light(vec3 normal, vec2 levels, float shadow)
+{
+    return max(sun_light(normal, shadow) * light_curve(levels.y), BLOCK_LIGHT_COLOR * light_curve(levels.x));
+}
@@ -2,11 +2,31 @@
 
 out vec4 Color;
 
-in vec2 textureCoordinate;
+in vec3 textureCoordinate;
+in vec3 normal;
+in float ambientOcclusion;
+in vec3 light; // block light, skylight, emission
+in vec3 worldPosition;
+in float viewDepth;
+in float viewDistance;
 
-uniform sampler2D custom_texture;
+uniform sampler2DArray custom_texture;
+uniform float u_alpha_cutoff;
+
+const float EMISSIVE_STRENGTH = 2.5; // pushes glowing blocks past 1.0 so bloom picks them up
+
+#include "common.glsl"
+#include "lighting.glsl"
+#include "fog.glsl"
 
 void main()
 {
-    Color = texture(custom_texture, textureCoordinate);
-}
\ No newline at end of file
+    vec4 color = texture(custom_texture, textureCoordinate);
+    if (color.a < u_alpha_cutoff)
+        discard;
+    float occlusion = mix(0.4, 1.0, ambientOcclusion);
+    float shadow = sun_shadow(worldPosition, normalize(normal), viewDepth);
+    vec3 lit = color.rgb * voxel_light(normalize(normal), light.xy, shadow) * occlusion;
+    lit += color.rgb * light.z * EMISSIVE_STRENGTH;
+    Color = vec4(apply_fog(lit, viewDistance), color.a);
+}
@@ -1,16 +1,31 @@
 #version 330 core
 layout (location = 0) in vec3 Position;
 layout (location = 1) in vec2 inTextureCoordinate;
+layout (location = 2) in float inTextureLayer;
+layout (location = 3) in vec3 inNormal;
+layout (location = 4) in float inAmbientOcclusion;
+layout (location = 5) in vec3 inLight; // block light, skylight, emission
 
-out vec2 textureCoordinate;
+out vec3 textureCoordinate;
+out vec3 normal;
+out float ambientOcclusion;
+out vec3 light;
+out vec3 worldPosition;
+out float viewDepth;
+out float viewDistance;
 
-uniform mat4 u_matrix_projection;
-uniform mat4 u_matrix_camera;
-uniform mat4 u_matrix_transform;
+#include "common.glsl"
 
 
 void main()
 {
-    textureCoordinate = inTextureCoordinate;
-    gl_Position = u_matrix_projection * u_matrix_camera * u_matrix_transform * vec4(Position, 1.0);
-}
\ No newline at end of file
+    textureCoordinate = vec3(inTextureCoordinate, inTextureLayer);
+    normal = inNormal; // chunk vertices are already in world space
+    ambientOcclusion = inAmbientOcclusion;
+    light = inLight;
+    worldPosition = Position;
+    vec4 viewPosition = u_matrix_camera * vec4(Position, 1.0);
+    viewDepth = -viewPosition.z;
+    viewDistance = length(viewPosition.xyz);
+    gl_Position = u_matrix_projection * viewPosition;
+}
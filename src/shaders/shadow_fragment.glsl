@@ -0,0 +1,12 @@
+#version 330 core
+
+in vec3 textureCoordinate;
+
+uniform sampler2DArray custom_texture;
+uniform float u_alpha_cutoff;
+
+void main()
+{
+    if (texture(custom_texture, textureCoordinate).a < u_alpha_cutoff)
+        discard;
+}
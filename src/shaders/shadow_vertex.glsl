@@ -0,0 +1,14 @@
+#version 330 core
+layout (location = 0) in vec3 Position;
+layout (location = 1) in vec2 inTextureCoordinate;
+layout (location = 2) in float inTextureLayer;
+
+out vec3 textureCoordinate;
+
+uniform mat4 u_light_matrix;
+
+void main()
+{
+    textureCoordinate = vec3(inTextureCoordinate, inTextureLayer);
+    gl_Position = u_light_matrix * vec4(Position, 1.0);
+}
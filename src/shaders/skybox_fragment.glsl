@@ -6,6 +6,33 @@ in vec3 textureCoordinate;
 
 uniform samplerCube ourTexture;
 
+#include "lighting.glsl"
+
+const float CUBE_MAP_WEIGHT = 0.35; // how much of the cube map shows through the sky gradient by day
+const float SUN_SIZE = 0.9995;      // cosine of the disc's angular radius
+const float MOON_SIZE = 0.9997;
+
+// Sparse, fixed points on a grid of directions.
+float stars(vec3 direction)
+{
+    vec3 cell = floor(direction * 150.0);
+    float random = fract(sin(dot(cell, vec3(12.9898, 78.233, 37.719))) * 43758.5453);
+    return step(0.997, random);
+}
+
 void main() {
-    Color = texture(ourTexture, textureCoordinate);
-}
\ No newline at end of file
+    vec3 direction = normalize(textureCoordinate);
+    float daylight = u_sky_zenith.w;
+    float star_visibility = u_sky_horizon.w;
+
+    vec3 sky = mix(u_sky_horizon.rgb, u_sky_zenith.rgb, pow(max(direction.y, 0.0), 0.5));
+    // Fades out towards the horizon, so the sky there is exactly the fog colour.
+    sky = mix(sky, texture(ourTexture, direction).rgb, CUBE_MAP_WEIGHT * daylight * smoothstep(0.0, 0.3, direction.y));
+
+    vec3 sun = u_sun_position.xyz;
+    sky += stars(direction) * star_visibility * step(0.0, direction.y);
+    sky = mix(sky, vec3(1.0, 0.95, 0.8), smoothstep(SUN_SIZE - 0.0003, SUN_SIZE, dot(direction, sun)));
+    sky = mix(sky, vec3(0.85, 0.88, 0.95), smoothstep(MOON_SIZE - 0.0002, MOON_SIZE, dot(direction, -sun)));
+
+    Color = vec4(sky, 1.0);
+}
@@ -2,14 +2,13 @@
 layout (location=0) in vec3 inPosition;
 layout (location=1) in vec2 inTextureCoordinate;
 
-uniform mat4 u_matrix_projection;
-uniform mat4 u_matrix_camera;
-uniform mat4 u_matrix_transform;
+#include "common.glsl"
 
 out vec3 textureCoordinate;
 
 void main()
 {
     textureCoordinate = inPosition;
-    gl_Position = u_matrix_projection * u_matrix_camera * u_matrix_transform * vec4(inPosition, 1.0);
+    vec4 position = u_matrix_projection * u_matrix_camera * u_matrix_transform * vec4(inPosition, 1.0);
+    gl_Position = position.xyww; // depth 1.0, behind everything drawn in the opaque pass
 }
\ No newline at end of file
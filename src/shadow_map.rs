@@ -0,0 +1,142 @@
+use crate::camera::Camera;
+use gl::types::{GLenum, GLint, GLsizei, GLuint};
+use nalgebra_glm::{inverse, look_at, ortho, vec3, vec4, Mat4, Vec3};
+use std::ptr::null;
+
+pub const SHADOW_CASCADES: usize = 3; // at most 4, the split distances share one vec4
+pub const SHADOW_MAP_SIZE: u32 = 2048;
+pub const SHADOW_MAP_TEXTURE_UNIT: u32 = 1; // kept bound for every lit program
+
+const SHADOW_DISTANCE: f32 = 64.0; // nothing past this casts or receives shadows
+const SPLIT_LAMBDA: f32 = 0.75;    // 0 = uniform splits, 1 = logarithmic
+const CASTER_MARGIN: f32 = 32.0;   // how far outside a cascade's bounds blocks still cast into it
+
+// One depth texture array layer per cascade, rendered from the sun.
+pub struct ShadowMap {
+    framebuffer: GLuint,
+    depth_texture: GLuint
+}
+
+impl ShadowMap {
+    pub fn generate() -> Result<Self, &'static str> {
+        let mut framebuffer: GLuint = 0;
+        let mut depth_texture: GLuint = 0;
+
+        unsafe {
+            gl::GenTextures(1, &mut depth_texture);
+            gl::BindTexture(gl::TEXTURE_2D_ARRAY, depth_texture);
+            gl::TexImage3D(gl::TEXTURE_2D_ARRAY, 0, gl::DEPTH_COMPONENT24 as GLint, SHADOW_MAP_SIZE as GLsizei, SHADOW_MAP_SIZE as GLsizei, SHADOW_CASCADES as GLsizei, 0, gl::DEPTH_COMPONENT, gl::FLOAT, null());
+            // LINEAR with a compare mode gives 2x2 hardware PCF on every tap.
+            gl::TexParameteri(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_MIN_FILTER, gl::LINEAR as GLint);
+            gl::TexParameteri(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_MAG_FILTER, gl::LINEAR as GLint);
+            gl::TexParameteri(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_BORDER as GLint);
+            gl::TexParameteri(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_BORDER as GLint);
+            gl::TexParameterfv(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_BORDER_COLOR, [1.0f32, 1.0, 1.0, 1.0].as_ptr());
+            gl::TexParameteri(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_COMPARE_MODE, gl::COMPARE_REF_TO_TEXTURE as GLint);
+            gl::TexParameteri(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_COMPARE_FUNC, gl::LEQUAL as GLint);
+            gl::BindTexture(gl::TEXTURE_2D_ARRAY, 0);
+
+            gl::GenFramebuffers(1, &mut framebuffer);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, framebuffer);
+            gl::FramebufferTextureLayer(gl::FRAMEBUFFER, gl::DEPTH_ATTACHMENT, depth_texture, 0, 0);
+            gl::DrawBuffer(gl::NONE);
+            gl::ReadBuffer(gl::NONE);
+        }
+
+        let shadow_map = ShadowMap{ framebuffer, depth_texture };
+
+        let status: GLenum = unsafe { gl::CheckFramebufferStatus(gl::FRAMEBUFFER) };
+        unsafe { gl::BindFramebuffer(gl::FRAMEBUFFER, 0); }
+
+        if status != gl::FRAMEBUFFER_COMPLETE {
+            return Err("Shadow map framebuffer is not complete");
+        }
+
+        Ok(shadow_map)
+    }
+
+    // Binds the cascade's layer as the depth target and clears it. The caller restores its own framebuffer.
+    pub fn bind_cascade(&self, cascade: usize) {
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.framebuffer);
+            gl::FramebufferTextureLayer(gl::FRAMEBUFFER, gl::DEPTH_ATTACHMENT, self.depth_texture, 0, cascade as GLint);
+            gl::Viewport(0, 0, SHADOW_MAP_SIZE as GLsizei, SHADOW_MAP_SIZE as GLsizei);
+            gl::Clear(gl::DEPTH_BUFFER_BIT);
+        }
+    }
+
+    pub fn get_depth_texture(&self) -> GLuint {
+        self.depth_texture
+    }
+
+    pub fn delete(&self) {
+        unsafe {
+            gl::DeleteFramebuffers(1, &self.framebuffer);
+            gl::DeleteTextures(1, &self.depth_texture);
+        }
+    }
+}
+
+impl Drop for ShadowMap {
+    fn drop(&mut self) {
+        self.delete();
+    }
+}
+
+pub struct Cascade {
+    pub matrix: Mat4,    // world space to the cascade's clip space
+    pub split_far: f32   // view space distance where the next cascade takes over
+}
+
+// Splits the camera frustum (up to SHADOW_DISTANCE) and fits an orthographic sun projection around each slice.
+pub fn fit_cascades(camera: &Camera, sun_direction: Vec3) -> Vec<Cascade> {
+    let near = camera.get_near_plane();
+    let far = camera.get_far_plane().min(SHADOW_DISTANCE);
+    let tan_half_fov = (camera.get_fov_degree().to_radians() / 2.0).tan();
+    let aspect_ratio = camera.get_aspect_ratio();
+    let camera_to_world = inverse(&camera.get_camera_look_at());
+
+    let mut cascades = vec![];
+    let mut split_near = near;
+    for cascade in 1..=SHADOW_CASCADES {
+        let fraction = cascade as f32 / SHADOW_CASCADES as f32;
+        let logarithmic = near * (far / near).powf(fraction);
+        let uniform = near + (far - near) * fraction;
+        let split_far = SPLIT_LAMBDA * logarithmic + (1.0 - SPLIT_LAMBDA) * uniform;
+
+        let mut corners = vec![];
+        for distance in [split_near, split_far] {
+            let half_height = distance * tan_half_fov;
+            let half_width = half_height * aspect_ratio;
+            for (x, y) in [(-1.0, -1.0), (1.0, -1.0), (-1.0, 1.0), (1.0, 1.0)] {
+                corners.push((camera_to_world * vec4(x * half_width, y * half_height, -distance, 1.0)).xyz());
+            }
+        }
+
+        cascades.push(Cascade{ matrix: fit_slice(&corners, sun_direction), split_far });
+        split_near = split_far;
+    }
+    cascades
+}
+
+fn fit_slice(corners: &[Vec3], sun_direction: Vec3) -> Mat4 {
+    let center = corners.iter().fold(vec3(0.0, 0.0, 0.0), |sum, corner| sum + corner) / corners.len() as f32;
+
+    // A bounding sphere keeps the projection the same size however the camera turns,
+    // rounded so floating point noise doesn't change it either.
+    let radius = corners.iter().map(|corner| (corner - center).norm()).fold(0.0, f32::max);
+    let radius = (radius * 16.0).ceil() / 16.0;
+
+    let up = if sun_direction.y.abs() > 0.99 { vec3(0.0, 0.0, 1.0) } else { vec3(0.0, 1.0, 0.0) };
+    let view = look_at(&(center + sun_direction * (radius + CASTER_MARGIN)), &center, &up);
+    let mut projection = ortho(-radius, radius, -radius, radius, 0.0, 2.0 * radius + CASTER_MARGIN);
+
+    // Snap the projection to whole shadow map texels so static geometry keeps landing on the same
+    // texels while the camera moves, otherwise shadow edges shimmer.
+    let origin = projection * view * vec4(0.0, 0.0, 0.0, 1.0);
+    let texels = SHADOW_MAP_SIZE as f32 / 2.0;
+    projection[(0, 3)] += ((origin.x * texels).round() - origin.x * texels) / texels;
+    projection[(1, 3)] += ((origin.y * texels).round() - origin.y * texels) / texels;
+
+    projection * view
+}
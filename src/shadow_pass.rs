@@ -0,0 +1,75 @@
+use crate::object::{SHADOWS_BLOCK_BINDING, UBO};
+use crate::render_pass::{FrameContext, RenderPass, RenderStage, RenderState};
+use crate::shader_manager::{ProgramHandle, ShaderManager};
+use crate::shadow_map::{fit_cascades, ShadowMap, SHADOW_CASCADES, SHADOW_MAP_TEXTURE_UNIT};
+use gl::types::{GLint, GLuint};
+use nalgebra_glm::{vec4, Mat4};
+
+const ALPHA_CUTOFF: f32 = 0.5; // same as the block pass, so leaves cast leaf shaped shadows
+
+// Renders opaque and cutout blocks into every shadow cascade and publishes the cascade matrices
+// through the Shadows block in lighting.glsl.
+pub struct ShadowPass {
+    program: ProgramHandle,
+    shadow_map: ShadowMap,
+    shadows_buffer: UBO
+}
+
+impl ShadowPass {
+    pub fn new(shader_manager: &mut ShaderManager) -> Self {
+        let program = shader_manager.load_or_fallback("shaders/shadow_vertex.glsl", "shaders/shadow_fragment.glsl");
+        let shadow_map = ShadowMap::generate().unwrap();
+        let shadows_buffer = UBO::generate(SHADOW_CASCADES * size_of::<Mat4>() + 4 * size_of::<f32>(), SHADOWS_BLOCK_BINDING);
+
+        ShadowPass{ program, shadow_map, shadows_buffer }
+    }
+}
+
+impl RenderPass for ShadowPass {
+    fn get_stage(&self) -> RenderStage {
+        RenderStage::Shadow
+    }
+
+    fn get_state(&self) -> RenderState {
+        RenderState::shadow()
+    }
+
+    fn draw(&mut self, context: &mut FrameContext) {
+        let cascades = fit_cascades(context.camera, context.lighting.sun_direction);
+
+        let mut splits = vec4(0.0, 0.0, 0.0, 0.0);
+        for (index, cascade) in cascades.iter().enumerate() {
+            self.shadows_buffer.set_mat4(index * size_of::<Mat4>(), &cascade.matrix);
+            splits[index] = cascade.split_far;
+        }
+        self.shadows_buffer.set_vec4(SHADOW_CASCADES * size_of::<Mat4>(), &splits);
+
+        // The frame is drawn into whatever was bound before, put it back afterwards.
+        let mut previous_framebuffer: GLint = 0;
+        let mut previous_viewport: [GLint; 4] = [0; 4];
+        unsafe {
+            gl::GetIntegerv(gl::DRAW_FRAMEBUFFER_BINDING, &mut previous_framebuffer);
+            gl::GetIntegerv(gl::VIEWPORT, previous_viewport.as_mut_ptr());
+        }
+
+        let program = context.shader_manager.get(self.program);
+        program.use_program();
+        program.set_texture("custom_texture", 0);
+        program.set_float("u_alpha_cutoff", ALPHA_CUTOFF);
+        context.texture.activate_texture_array(gl::TEXTURE0, context.texture.get_id(context.chunk_renderer.get_block_textures()));
+
+        for (index, cascade) in cascades.iter().enumerate() {
+            self.shadow_map.bind_cascade(index);
+            program.set_mat4("u_light_matrix", &cascade.matrix);
+            context.chunk_renderer.draw_opaque();
+        }
+
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, previous_framebuffer as GLuint);
+            gl::Viewport(previous_viewport[0], previous_viewport[1], previous_viewport[2], previous_viewport[3]);
+        }
+
+        context.texture.activate_texture_array(gl::TEXTURE0 + SHADOW_MAP_TEXTURE_UNIT, self.shadow_map.get_depth_texture());
+        unsafe { gl::ActiveTexture(gl::TEXTURE0); }
+    }
+}
@@ -1,3 +1,5 @@
+use crate::vertex_layout::VertexLayout;
+
 pub struct ShapeData {
 
 }
@@ -40,6 +42,11 @@ impl ShapeData {
         vertices
     }
 
+    // position (location 0) + texture coordinate (location 1)
+    pub fn get_cube_layout() -> VertexLayout {
+        VertexLayout::new().float(0, 3).float(1, 2)
+    }
+
     pub fn get_cube_indices() -> Vec<u32> {
         let mut indices: Vec<u32> = vec![];
         for index in 0..6 {
@@ -64,6 +71,10 @@ impl ShapeData {
         ];
         crosshair_vertices
     }
+
+    pub fn get_crosshair_layout() -> VertexLayout {
+        VertexLayout::new().float(0, 2)
+    }
 }
 
 /*
@@ -0,0 +1,56 @@
+use crate::mesh::Mesh;
+use crate::render_pass::{FrameContext, RenderPass, RenderStage, RenderState};
+use crate::shader_manager::{ProgramHandle, ShaderManager};
+use crate::shape_data::ShapeData;
+use crate::texture::{Texture, TextureHandle};
+use crate::transform::Transform;
+use nalgebra_glm::vec3;
+
+pub struct SkyboxPass {
+    program: ProgramHandle,
+    mesh: Mesh,
+    cube_map: TextureHandle,
+    transform: Transform
+}
+
+const CUBE_MAP_FACES: [&str; 6] = ["textures/right.jpg", "textures/left.jpg", "textures/top.jpg", "textures/bottom.jpg", "textures/front.jpg", "textures/back.jpg"];
+
+impl SkyboxPass {
+    pub fn new(shader_manager: &mut ShaderManager, texture: &mut Texture) -> Self {
+        let cube_map = texture.load_cube_map_texture_or_fallback(CUBE_MAP_FACES.iter().map(|face| face.to_string()).collect());
+
+        let program = shader_manager.load_or_fallback("shaders/skybox_vertex.glsl", "shaders/skybox_fragment.glsl");
+        let mesh = Mesh::new(&ShapeData::get_cube_vertices(), Some(&ShapeData::get_cube_indices()), ShapeData::get_cube_layout());
+
+        SkyboxPass{ program, mesh, cube_map, transform: Transform::new() }
+    }
+}
+
+impl RenderPass for SkyboxPass {
+    fn get_stage(&self) -> RenderStage {
+        RenderStage::Skybox
+    }
+
+    fn get_state(&self) -> RenderState {
+        RenderState::skybox()
+    }
+
+    fn reload_textures(&mut self, texture: &mut Texture) {
+        texture.release_texture(self.cube_map);
+        self.cube_map = texture.load_cube_map_texture_or_fallback(CUBE_MAP_FACES.iter().map(|face| face.to_string()).collect());
+    }
+
+    fn draw(&mut self, context: &mut FrameContext) {
+        self.transform.set_position(context.camera.get_camera_position());
+        self.transform.set_scale(vec3(2.0, 2.0, 2.0));
+        self.transform.set_euler_angles(vec3(0.0, 0.0, 0.0));
+        self.transform.update();
+
+        let program = context.shader_manager.get(self.program);
+        program.use_program();
+        program.set_mat4("u_matrix_transform", &self.transform.get_matrix());
+
+        context.texture.activate_cube_map_texture(context.texture.get_id(self.cube_map));
+        self.mesh.draw(gl::TRIANGLES);
+    }
+}
@@ -1,68 +1,358 @@
 use std::collections::HashMap;
-use std::path::Path;
-use gl::types::{GLenum, GLuint};
-use image::GenericImageView;
+use std::ffi::CStr;
+use std::rc::Rc;
+use gl::types::{GLenum, GLsizei, GLuint};
+use image::imageops::{self, FilterType};
+use image::{DynamicImage, Rgba, RgbaImage};
+use crate::error::AssetError;
+use crate::lighting::{linear_to_srgb, srgb_to_linear};
+use crate::resource_pack::AssetResolver;
+use crate::texture_animation::TextureAnimation;
 
+const FALLBACK_CUBE_MAP_TEXTURE: &str = "<fallback cube map>";
+
+// Core since 4.6, the same values as ARB/EXT_texture_filter_anisotropic before that.
+const TEXTURE_MAX_ANISOTROPY: GLenum = 0x84FE;
+const MAX_TEXTURE_MAX_ANISOTROPY: GLenum = 0x84FF;
+const ANISOTROPY_EXTENSIONS: &[&str] = &["GL_ARB_texture_filter_anisotropic", "GL_EXT_texture_filter_anisotropic"];
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TextureFilter {
+    Nearest,   // no mipmaps, shimmers in the distance
+    Pixelated, // sharp texels up close, blended mipmaps further away
+    Trilinear  // smooth everywhere
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct TextureFiltering {
+    pub filter: TextureFilter,
+    pub anisotropy: f32 // 1 is off, clamped to what the driver supports
+}
+
+impl TextureFiltering {
+    pub fn new() -> Self {
+        TextureFiltering{ filter: TextureFilter::Pixelated, anisotropy: 8.0 }
+    }
+}
+
+// Refers to a texture owned by a Texture manager. Handles of released textures stop working
+// instead of silently pointing at whatever reuses the slot.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct TextureHandle {
+    index: usize,
+    generation: u32
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct TextureInfo {
+    pub target: GLenum,          // TEXTURE_2D, TEXTURE_2D_ARRAY or TEXTURE_CUBE_MAP
+    pub internal_format: GLenum, // RGBA8 or SRGB8_ALPHA8
+    pub width: u32,
+    pub height: u32,
+    pub layers: u32              // 1 except for arrays
+}
+
+struct TextureEntry {
+    id: GLuint,
+    info: TextureInfo,
+    key: Option<String>, // cache key while other loads can still share it
+    references: u32
+}
+
+struct TextureSlot {
+    generation: u32,
+    entry: Option<TextureEntry>
+}
+
+// Handle bookkeeping, reference counts and the cache, kept apart from GL. Removing an entry hands
+// back its texture name for the caller to delete.
+struct TextureSlots {
+    slots: Vec<TextureSlot>,
+    cache: HashMap<String, TextureHandle> // path(s): texture
+}
+
+impl TextureSlots {
+    fn new() -> Self {
+        TextureSlots{ slots: vec![], cache: HashMap::new() }
+    }
+
+    fn insert(&mut self, id: GLuint, info: TextureInfo, key: Option<String>) -> TextureHandle {
+        let entry = TextureEntry{ id, info, key: key.clone(), references: 1 };
+        let index = match self.slots.iter().position(|slot| slot.entry.is_none()) {
+            Some(index) => index,
+            None => {
+                self.slots.push(TextureSlot{ generation: 0, entry: None });
+                self.slots.len() - 1
+            }
+        };
+        self.slots[index].entry = Some(entry);
+        let handle = TextureHandle{ index, generation: self.slots[index].generation };
+        if let Some(key) = key {
+            self.cache.insert(key, handle);
+        }
+        handle
+    }
+
+    // Another reference to a cached texture.
+    fn acquire(&mut self, key: &str) -> Option<TextureHandle> {
+        let handle = *self.cache.get(key)?;
+        self.get_mut(handle).references += 1;
+        Some(handle)
+    }
+
+    fn get(&self, handle: TextureHandle) -> &TextureEntry {
+        self.slots.get(handle.index).filter(|slot| slot.generation == handle.generation).and_then(|slot| slot.entry.as_ref())
+            .expect("Texture handle used after its texture was released")
+    }
+
+    fn get_mut(&mut self, handle: TextureHandle) -> &mut TextureEntry {
+        self.slots.get_mut(handle.index).filter(|slot| slot.generation == handle.generation).and_then(|slot| slot.entry.as_mut())
+            .expect("Texture handle used after its texture was released")
+    }
+
+    // Nothing cached so far is handed out again. Unreferenced entries stay until take_unused.
+    fn clear_cache(&mut self) {
+        self.cache.clear();
+        for slot in self.slots.iter_mut() {
+            if let Some(entry) = &mut slot.entry {
+                entry.key = None;
+            }
+        }
+    }
+
+    // Returns the texture name once the last reference to an uncached texture is gone.
+    fn release(&mut self, handle: TextureHandle) -> Option<GLuint> {
+        let entry = self.get_mut(handle);
+        assert!(entry.references > 0, "Texture released more often than it was acquired");
+        entry.references -= 1;
+        if entry.references == 0 && entry.key.is_none() {
+            return self.remove(handle.index);
+        }
+        None
+    }
+
+    // Removes every entry nobody holds a reference to, returns their texture names.
+    fn take_unused(&mut self) -> Vec<GLuint> {
+        let unused: Vec<usize> = self.slots.iter().enumerate()
+            .filter(|(_, slot)| slot.entry.as_ref().is_some_and(|entry| entry.references == 0))
+            .map(|(index, _)| index)
+            .collect();
+        unused.into_iter().filter_map(|index| self.remove(index)).collect()
+    }
+
+    fn take_all(&mut self) -> Vec<GLuint> {
+        (0..self.slots.len()).filter_map(|index| self.remove(index)).collect()
+    }
+
+    fn remove(&mut self, index: usize) -> Option<GLuint> {
+        let slot = &mut self.slots[index];
+        let entry = slot.entry.take()?;
+        if let Some(key) = entry.key {
+            self.cache.remove(&key);
+        }
+        slot.generation += 1;
+        Some(entry.id)
+    }
+}
+
+// Owns every texture loaded from the resource packs. Cube maps are cached by path and reference
+// counted, loading the same files twice hands out the same texture. A texture is deleted once
+// released by every owner: arrays right away, cached ones by unload_unused so a texture that is
+// needed again soon doesn't have to be decoded twice.
 pub struct Texture {
-    texture_list: HashMap<String, GLuint> // file name: texture id
+    assets: Rc<AssetResolver>,
+    slots: TextureSlots,
+    srgb: bool, // images are authored in sRGB and decoded to linear when sampled, false uploads plain RGBA8 for the old gamma space look
+    filtering: TextureFiltering,
+    max_anisotropy: f32 // 1 without anisotropic filtering support
 }
 
 impl Texture {
     
-    pub fn new() -> Self {
-        Texture{ texture_list: HashMap::new() }
+    pub fn new(assets: Rc<AssetResolver>, srgb: bool) -> Self {
+        Texture{ assets, slots: TextureSlots::new(), srgb, filtering: TextureFiltering::new(), max_anisotropy: query_max_anisotropy() }
+    }
+
+    // Used for textures loaded from now on, the owners of loaded ones reload them. Nothing loaded
+    // from the old packs is handed out again, unused textures are deleted right away.
+    pub fn set_assets(&mut self, assets: Rc<AssetResolver>) {
+        self.assets = assets;
+        self.slots.clear_cache();
+        self.unload_unused();
+    }
+
+    // Like set_assets, the owners of loaded textures reload them to switch.
+    pub fn set_srgb(&mut self, srgb: bool) {
+        self.srgb = srgb;
+        self.slots.clear_cache();
+        self.unload_unused();
+    }
+
+    fn read_image(&self, file_path: &str) -> Result<DynamicImage, AssetError> {
+        let bytes = self.assets.read(file_path)?;
+        image::load_from_memory(&bytes).map_err(|error| AssetError::from_image(file_path, error))
+    }
+
+    pub fn get_id(&self, handle: TextureHandle) -> GLuint {
+        self.slots.get(handle).id
+    }
+
+    pub fn get_info(&self, handle: TextureHandle) -> TextureInfo {
+        self.slots.get(handle).info
+    }
+
+    // Used for textures loaded from now on, already loaded ones are updated through apply_filtering.
+    pub fn set_filtering(&mut self, filtering: TextureFiltering) {
+        self.filtering = filtering;
+    }
+
+    pub fn get_filtering(&self) -> TextureFiltering {
+        self.filtering
+    }
+
+    // Sets the sampling parameters of a mipmapped 2D texture or texture array.
+    pub fn apply_filtering(&self, handle: TextureHandle) {
+        let entry = self.slots.get(handle);
+        self.apply_filtering_to(entry.info.target, entry.id);
+    }
+
+    fn apply_filtering_to(&self, target: GLenum, texture_id: GLuint) {
+        let (min_filter, mag_filter) = match self.filtering.filter {
+            TextureFilter::Nearest => (gl::NEAREST, gl::NEAREST),
+            TextureFilter::Pixelated => (gl::NEAREST_MIPMAP_LINEAR, gl::NEAREST),
+            TextureFilter::Trilinear => (gl::LINEAR_MIPMAP_LINEAR, gl::LINEAR)
+        };
+        unsafe {
+            gl::BindTexture(target, texture_id);
+            gl::TexParameteri(target, gl::TEXTURE_MIN_FILTER, min_filter as i32);
+            gl::TexParameteri(target, gl::TEXTURE_MAG_FILTER, mag_filter as i32);
+            if self.max_anisotropy > 1.0 {
+                gl::TexParameterf(target, TEXTURE_MAX_ANISOTROPY, self.filtering.anisotropy.clamp(1.0, self.max_anisotropy));
+            }
+            gl::BindTexture(target, 0);
+        }
+    }
+
+    fn get_internal_format(&self) -> GLenum {
+        if self.srgb { gl::SRGB8_ALPHA8 } else { gl::RGBA8 }
     }
-    pub fn load_texture(&mut self, file_path: &str) -> GLuint {
-        let img = image::open(&Path::new(file_path)).expect(format!("Failed to load texture: {}", file_path).as_str());
-        let data = img.flipv().to_rgba8();
-        let (width, height) = img.dimensions();
-        let byte_array = data.into_raw();
 
+    // One GL_TEXTURE_2D_ARRAY layer per file, in order. Layers that fail to load are logged and
+    // replaced by the checker, images of a different size are scaled to the first one. Images with an
+    // animation sidecar start on their first frame, the returned animations update them later.
+    // Arrays aren't cached, their layers are the owner's to change.
+    pub fn load_texture_array(&mut self, file_paths: &[&str]) -> (TextureHandle, Vec<TextureAnimation>) {
+        let mut animations = vec![];
+        let images: Vec<Option<RgbaImage>> = file_paths.iter().enumerate().map(|(layer, file_path)| {
+            let img = match self.read_image(file_path) {
+                Ok(img) => img.flipv().to_rgba8(),
+                Err(error) => {
+                    eprintln!("{}", error);
+                    return None;
+                }
+            };
+            // The sidecar has to come from the same pack as the image it describes.
+            match self.assets.find(file_path).map_or(Ok(None), |pack| TextureAnimation::load(pack, file_path, layer, &img)) {
+                Ok(Some(animation)) => {
+                    let frame = animation.get_frame().clone();
+                    animations.push(animation);
+                    Some(frame)
+                },
+                Ok(None) => Some(img),
+                Err(error) => {
+                    eprintln!("{}", error);
+                    Some(img)
+                }
+            }
+        }).collect();
+
+        let (width, height) = images.iter().flatten().next().map_or((16, 16), |img| img.dimensions());
+        let layers: Vec<RgbaImage> = images.into_iter().map(|img| {
+            let img = img.unwrap_or_else(create_checker_image);
+            if img.dimensions() == (width, height) { img } else { imageops::resize(&img, width, height, FilterType::Nearest) }
+        }).collect();
+        for animation in animations.iter_mut() {
+            animation.resize(width, height);
+        }
+
+        let internal_format = self.get_internal_format();
         let mut texture_id = 0;
         unsafe {
             gl::GenTextures(1, &mut texture_id);
-            gl::BindTexture(gl::TEXTURE_2D, texture_id);
+            gl::BindTexture(gl::TEXTURE_2D_ARRAY, texture_id);
+
+            gl::TexParameteri(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_WRAP_S, gl::REPEAT as i32);
+            gl::TexParameteri(gl::TEXTURE_2D_ARRAY, gl::TEXTURE_WRAP_T, gl::REPEAT as i32);
 
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::REPEAT as i32);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::REPEAT as i32);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST  as i32);
-            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST  as i32);
+            gl::TexStorage3D(gl::TEXTURE_2D_ARRAY, mip_level_count(width, height) as GLsizei, internal_format, width as i32, height as i32, layers.len() as i32);
+            gl::BindTexture(gl::TEXTURE_2D_ARRAY, 0);
+        }
+        let info = TextureInfo{ target: gl::TEXTURE_2D_ARRAY, internal_format, width, height, layers: layers.len() as u32 };
+        let handle = self.slots.insert(texture_id, info, None);
+        self.update_texture_array(handle, layers.iter().enumerate());
+        self.apply_filtering(handle);
+        (handle, animations)
+    }
 
-            gl::TexImage2D(
-                gl::TEXTURE_2D,
-                0,
-                gl::RGBA as i32,
-                width as i32,
-                height as i32,
-                0,
-                gl::RGBA,
-                gl::UNSIGNED_BYTE,
-                byte_array.as_ptr() as *const _,
-            );
-            gl::GenerateMipmap(gl::TEXTURE_2D);
+    // Replaces whole layers of a texture array together with their mipmaps. Each layer gets its own
+    // mip chain, so unlike an atlas no tile ever blends into its neighbours. The mips are built here
+    // instead of with glGenerateMipmap, which would rebuild every layer.
+    pub fn update_texture_array<'a>(&self, handle: TextureHandle, layers: impl Iterator<Item = (usize, &'a RgbaImage)>) {
+        let info = self.get_info(handle);
+        assert_eq!(info.target, gl::TEXTURE_2D_ARRAY, "Only texture arrays have layers");
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_2D_ARRAY, self.get_id(handle));
+            for (layer, img) in layers {
+                assert!(layer < info.layers as usize && img.dimensions() == (info.width, info.height), "Layer {} doesn't fit the texture array", layer);
+                for (level, mip) in create_mip_chain(img, info.internal_format == gl::SRGB8_ALPHA8).iter().enumerate() {
+                    let (width, height) = mip.dimensions();
+                    gl::TexSubImage3D(gl::TEXTURE_2D_ARRAY, level as i32, 0, 0, layer as i32, width as i32, height as i32, 1, gl::RGBA, gl::UNSIGNED_BYTE, mip.as_raw().as_ptr() as *const _);
+                }
+            }
+            gl::BindTexture(gl::TEXTURE_2D_ARRAY, 0);
+        }
+    }
 
-            gl::BindTexture(gl::TEXTURE_2D, 0);
+    pub fn load_cube_map_texture(&mut self, faces: Vec<String>) -> Result<TextureHandle, AssetError> {
+        let key = faces.join("|");
+        if let Some(handle) = self.slots.acquire(&key) {
+            return Ok(handle);
+        }
+        let mut images = vec![];
+        for face in &faces {
+            images.push(self.read_image(face)?.to_rgba8());
         }
-        self.texture_list.insert(file_path.to_string(), texture_id);
-        texture_id
+        let (id, info) = self.upload_cube_map_texture(&images);
+        Ok(self.slots.insert(id, info, Some(key)))
     }
 
-    pub fn load_cube_map_texture(&mut self, faces: Vec<String>) -> GLuint {
+    pub fn load_cube_map_texture_or_fallback(&mut self, faces: Vec<String>) -> TextureHandle {
+        self.load_cube_map_texture(faces).unwrap_or_else(|error| {
+            eprintln!("{}", error);
+            if let Some(handle) = self.slots.acquire(FALLBACK_CUBE_MAP_TEXTURE) {
+                return handle;
+            }
+            let checker = create_checker_image();
+            let (id, info) = self.upload_cube_map_texture(&vec![checker; 6]);
+            self.slots.insert(id, info, Some(FALLBACK_CUBE_MAP_TEXTURE.to_string()))
+        })
+    }
+
+    fn upload_cube_map_texture(&self, faces: &[RgbaImage]) -> (GLuint, TextureInfo) {
+        let internal_format = self.get_internal_format();
         let mut texture_id = 0;
         unsafe {
             gl::GenTextures(1, &mut texture_id);
             gl::BindTexture(gl::TEXTURE_CUBE_MAP, texture_id);
         }
-        for index in 0..faces.len() {
-            let img = image::open(&Path::new(&faces[index])).expect(format!("Failed to load texture: {}", faces[index]).as_str());
-            let data = img.to_rgba8();
-            let (width, height) = img.dimensions();
-            let byte_array = data.into_raw();
+        for (index, data) in faces.iter().enumerate() {
+            let (width, height) = data.dimensions();
+            let byte_array = data.as_raw();
             unsafe {
                 gl::TexImage2D(
                     gl::TEXTURE_CUBE_MAP_POSITIVE_X + index as GLenum,
                     0,
-                    gl::RGBA as i32,
+                    internal_format as i32,
                     width as i32,
                     height as i32,
                     0,
@@ -77,7 +367,22 @@ impl Texture {
                 gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
             }
         }
-        texture_id
+        let (width, height) = faces.first().map_or((0, 0), |face| face.dimensions());
+        (texture_id, TextureInfo{ target: gl::TEXTURE_CUBE_MAP, internal_format, width, height, layers: 6 })
+    }
+
+    // Gives up one reference, the handle can't be used afterwards.
+    pub fn release_texture(&mut self, handle: TextureHandle) {
+        if let Some(id) = self.slots.release(handle) {
+            unsafe { gl::DeleteTextures(1, &id); }
+        }
+    }
+
+    // Deletes the cached textures nobody holds a reference to any more, returns how many.
+    pub fn unload_unused(&mut self) -> usize {
+        let unused = self.slots.take_unused();
+        delete_textures(&unused);
+        unused.len()
     }
 
     pub fn activate_texture(&self, texture_level: GLenum, texture_id: GLuint) { // TEXTURE0,1,2..15, GLuint
@@ -87,6 +392,13 @@ impl Texture {
         }
     }
 
+    pub fn activate_texture_array(&self, texture_level: GLenum, texture_id: GLuint) {
+        unsafe {
+            gl::ActiveTexture(texture_level);
+            gl::BindTexture(gl::TEXTURE_2D_ARRAY, texture_id);
+        }
+    }
+
     pub fn activate_cube_map_texture(&self, texture_id: GLuint) {
         unsafe {
             gl::ActiveTexture(gl::TEXTURE_CUBE_MAP);
@@ -97,10 +409,174 @@ impl Texture {
     pub fn deactivate_texture(&self) {
         unsafe { gl::BindTexture(gl::TEXTURE_2D, 0); }
     }
+}
 
-    pub fn delete_texture(&self, texture_id: GLuint) {
-        unsafe {
-            gl::DeleteTextures(1, texture_id as *const GLuint);
-        }
+impl Drop for Texture {
+    fn drop(&mut self) {
+        delete_textures(&self.slots.take_all());
+    }
+}
+
+fn delete_textures(ids: &[GLuint]) {
+    if !ids.is_empty() {
+        unsafe { gl::DeleteTextures(ids.len() as GLsizei, ids.as_ptr()); }
+    }
+}
+
+fn query_max_anisotropy() -> f32 {
+    let mut extension_count = 0;
+    unsafe { gl::GetIntegerv(gl::NUM_EXTENSIONS, &mut extension_count); }
+    let supported = (0..extension_count as GLuint).any(|index| {
+        let name = unsafe { CStr::from_ptr(gl::GetStringi(gl::EXTENSIONS, index) as *const _) };
+        name.to_str().is_ok_and(|name| ANISOTROPY_EXTENSIONS.contains(&name))
+    });
+    if !supported {
+        return 1.0;
+    }
+    let mut max_anisotropy = 1.0;
+    unsafe { gl::GetFloatv(MAX_TEXTURE_MAX_ANISOTROPY, &mut max_anisotropy); }
+    max_anisotropy
+}
+
+fn mip_level_count(width: u32, height: u32) -> u32 {
+    32 - width.max(height).max(1).leading_zeros()
+}
+
+// Every mip level down to 1x1, starting with the image itself. Each texel averages the 2x2 texels
+// above it, sRGB colours in linear space like the GPU would.
+fn create_mip_chain(img: &RgbaImage, srgb: bool) -> Vec<RgbaImage> {
+    let decode: Vec<f32> = (0..=255).map(|value| {
+        let value = value as f32 / 255.0;
+        if srgb { srgb_to_linear(value) } else { value }
+    }).collect();
+    let encode = |value: f32| {
+        let value = if srgb { linear_to_srgb(value) } else { value };
+        (value * 255.0).round() as u8
+    };
+
+    let mut chain = vec![img.clone()];
+    for _ in 1..mip_level_count(img.width(), img.height()) {
+        let previous = chain.last().unwrap();
+        let (width, height) = ((previous.width() / 2).max(1), (previous.height() / 2).max(1));
+        let mip = RgbaImage::from_fn(width, height, |x, y| {
+            let texels = [(0, 0), (1, 0), (0, 1), (1, 1)].map(|(offset_x, offset_y)| {
+                previous.get_pixel((2 * x + offset_x).min(previous.width() - 1), (2 * y + offset_y).min(previous.height() - 1))
+            });
+            let mut texel = [0; 4];
+            for channel in 0..3 {
+                texel[channel] = encode(texels.iter().map(|texel| decode[texel[channel] as usize]).sum::<f32>() / 4.0);
+            }
+            texel[3] = (texels.iter().map(|texel| texel[3] as u32).sum::<u32>() as f32 / 4.0).round() as u8;
+            Rgba(texel)
+        });
+        chain.push(mip);
+    }
+    chain
+}
+
+// 8x8 magenta/black checker, impossible to miss in game.
+fn create_checker_image() -> RgbaImage {
+    RgbaImage::from_fn(8, 8, |x, y| {
+        if (x + y) % 2 == 0 { Rgba([255, 0, 255, 255]) } else { Rgba([0, 0, 0, 255]) }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INFO: TextureInfo = TextureInfo{ target: gl::TEXTURE_2D, internal_format: gl::RGBA8, width: 16, height: 16, layers: 1 };
+
+    #[test]
+    fn uncached_textures_are_removed_on_release() {
+        let mut slots = TextureSlots::new();
+        let handle = slots.insert(7, INFO, None);
+        assert_eq!(slots.get(handle).id, 7);
+        assert_eq!(slots.release(handle), Some(7));
+        assert!(slots.take_all().is_empty());
+    }
+
+    #[test]
+    fn cached_textures_are_shared_until_unused() {
+        let mut slots = TextureSlots::new();
+        let handle = slots.insert(3, INFO, Some("sky".to_string()));
+        assert_eq!(slots.acquire("sky"), Some(handle));
+        assert_eq!(slots.acquire("ground"), None);
+
+        assert_eq!(slots.release(handle), None);
+        assert!(slots.take_unused().is_empty()); // still referenced once
+        assert_eq!(slots.release(handle), None); // kept for take_unused
+        assert_eq!(slots.acquire("sky"), Some(handle));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn unused_cached_textures_are_taken_once() {
+        let mut slots = TextureSlots::new();
+        let used = slots.insert(1, INFO, Some("used".to_string()));
+        let unused = slots.insert(2, INFO, Some("unused".to_string()));
+        assert_eq!(slots.release(unused), None);
+
+        assert_eq!(slots.take_unused(), vec![2]);
+        assert!(slots.take_unused().is_empty());
+        assert_eq!(slots.acquire("unused"), None);
+        assert_eq!(slots.get(used).id, 1);
+        assert_eq!(slots.take_all(), vec![1]);
+    }
+
+    #[test]
+    fn clearing_the_cache_keeps_loaded_textures() {
+        let mut slots = TextureSlots::new();
+        let handle = slots.insert(4, INFO, Some("sky".to_string()));
+        slots.clear_cache();
+        assert_eq!(slots.acquire("sky"), None);
+        assert_eq!(slots.get(handle).id, 4);
+        assert_eq!(slots.release(handle), Some(4)); // no longer cached, removed right away
+    }
+
+    #[test]
+    fn reused_slots_get_a_new_generation() {
+        let mut slots = TextureSlots::new();
+        let old = slots.insert(1, INFO, None);
+        slots.release(old);
+        let new = slots.insert(2, INFO, None);
+        assert_eq!(new.index, old.index);
+        assert_ne!(new, old);
+        assert_eq!(slots.get(new).id, 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "after its texture was released")]
+    fn stale_handles_panic() {
+        let mut slots = TextureSlots::new();
+        let old = slots.insert(1, INFO, None);
+        slots.release(old);
+        slots.insert(2, INFO, None);
+        slots.get(old);
+    }
+
+    #[test]
+    #[should_panic(expected = "released more often")]
+    fn double_release_panics() {
+        let mut slots = TextureSlots::new();
+        let handle = slots.insert(1, INFO, Some("sky".to_string()));
+        slots.release(handle);
+        slots.release(handle);
+    }
+
+    #[test]
+    fn mip_chains_go_down_to_one_texel() {
+        assert_eq!(mip_level_count(16, 16), 5);
+        assert_eq!(mip_level_count(16, 4), 5);
+        assert_eq!(mip_level_count(1, 1), 1);
+
+        let sizes: Vec<(u32, u32)> = create_mip_chain(&RgbaImage::new(8, 2), false).iter().map(|mip| mip.dimensions()).collect();
+        assert_eq!(sizes, vec![(8, 2), (4, 1), (2, 1), (1, 1)]);
+    }
+
+    #[test]
+    fn mips_average_in_linear_space() {
+        let img = RgbaImage::from_fn(2, 2, |x, _| if x == 0 { Rgba([255, 255, 255, 255]) } else { Rgba([0, 0, 0, 0]) });
+        assert_eq!(create_mip_chain(&img, false)[1].get_pixel(0, 0), &Rgba([128, 128, 128, 128]));
+        assert_eq!(create_mip_chain(&img, true)[1].get_pixel(0, 0), &Rgba([188, 188, 188, 128])); // half the light, not half the value
+    }
+}
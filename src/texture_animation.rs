@@ -0,0 +1,157 @@
+use crate::error::AssetError;
+use crate::resource_pack::ResourcePack;
+use image::imageops::{self, FilterType};
+use image::RgbaImage;
+
+pub const ANIMATION_EXTENSION: &str = "anim"; // water.png is animated by water.png.anim
+
+const DEFAULT_FRAME_TIME: f32 = 0.1; // seconds
+
+// Sidecar format, one setting per line, # starts a comment:
+//   frame_time 0.15        seconds per frame unless a frame says otherwise
+//   frames 0 1 2 3:0.5 2   frame order, index or index:seconds, defaults to every frame top to bottom
+struct AnimationFrame {
+    index: usize,
+    duration: f32
+}
+
+// One texture array layer cycling through the square frames of a vertical strip image.
+pub struct TextureAnimation {
+    layer: usize,
+    frames: Vec<RgbaImage>,
+    timeline: Vec<AnimationFrame>,
+    step: usize,
+    elapsed: f32
+}
+
+impl TextureAnimation {
+    // None when the image has no sidecar file, it's then used as a plain texture. The strip is expected
+    // flipped like every other image, so the top frame (frame 0) is at the bottom of it.
+    pub fn load(pack: &ResourcePack, file_path: &str, layer: usize, strip: &RgbaImage) -> Result<Option<Self>, AssetError> {
+        let metadata_asset = format!("{}.{}", file_path, ANIMATION_EXTENSION);
+        let source = match pack.read(&metadata_asset) {
+            Some(source) => String::from_utf8_lossy(&source?).into_owned(),
+            None => return Ok(None)
+        };
+        let metadata_path = pack.describe(&metadata_asset);
+
+        let (width, height) = strip.dimensions();
+        let frame_count = (height / width.max(1)) as usize;
+        if frame_count == 0 || height % width != 0 {
+            return Err(AssetError::Metadata{ path: metadata_path, line: 0, message: format!("{}x{} isn't a strip of square frames", width, height) });
+        }
+        let frames: Vec<RgbaImage> = (0..frame_count)
+            .map(|frame| imageops::crop_imm(strip, 0, height - (frame as u32 + 1) * width, width, width).to_image())
+            .collect();
+
+        let timeline = parse_metadata(&source, frame_count).map_err(|(line, message)| AssetError::Metadata{ path: metadata_path, line, message })?;
+        Ok(Some(TextureAnimation{ layer, frames, timeline, step: 0, elapsed: 0.0 }))
+    }
+
+    pub fn get_layer(&self) -> usize {
+        self.layer
+    }
+
+    pub fn get_frame(&self) -> &RgbaImage {
+        &self.frames[self.timeline[self.step].index]
+    }
+
+    // Texture arrays need every layer the same size.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        for frame in self.frames.iter_mut() {
+            if frame.dimensions() != (width, height) {
+                *frame = imageops::resize(frame, width, height, FilterType::Nearest);
+            }
+        }
+    }
+
+    // Returns true when the frame changed and the layer has to be uploaded again.
+    pub fn update(&mut self, delta_time: f32) -> bool {
+        let previous = self.timeline[self.step].index;
+        self.elapsed += delta_time;
+        // Long frame hitches skip frames instead of playing them all at once.
+        while self.elapsed >= self.timeline[self.step].duration {
+            self.elapsed -= self.timeline[self.step].duration;
+            self.step = (self.step + 1) % self.timeline.len();
+        }
+        self.timeline[self.step].index != previous
+    }
+}
+
+fn parse_metadata(source: &str, frame_count: usize) -> Result<Vec<AnimationFrame>, (u32, String)> {
+    let mut frame_time = DEFAULT_FRAME_TIME;
+    let mut order: Option<Vec<(usize, Option<f32>)>> = None;
+
+    for (number, line) in source.lines().enumerate() {
+        let number = number as u32 + 1;
+        let line = line.split('#').next().unwrap().trim();
+        let mut words = line.split_whitespace();
+        match words.next() {
+            None => continue,
+            Some("frame_time") => {
+                frame_time = words.next().and_then(|value| value.parse().ok()).filter(|time: &f32| *time > 0.0)
+                    .ok_or((number, "frame_time needs a positive number of seconds".to_string()))?;
+            },
+            Some("frames") => {
+                let mut frames = vec![];
+                for word in words.by_ref() {
+                    let (index, duration) = match word.split_once(':') {
+                        Some((index, duration)) => (index, Some(duration.parse().ok().filter(|time: &f32| *time > 0.0).ok_or((number, format!("Invalid frame duration: {}", word)))?)),
+                        None => (word, None)
+                    };
+                    let index: usize = index.parse().ok().filter(|index| *index < frame_count).ok_or((number, format!("Invalid frame: {} (the strip has {} frames)", word, frame_count)))?;
+                    frames.push((index, duration));
+                }
+                if frames.is_empty() {
+                    return Err((number, "frames needs at least one frame".to_string()));
+                }
+                order = Some(frames);
+            },
+            Some(key) => return Err((number, format!("Unknown setting: {}", key)))
+        }
+        if words.next().is_some() {
+            return Err((number, "Unexpected value at the end of the line".to_string()));
+        }
+    }
+
+    let order = order.unwrap_or_else(|| (0..frame_count).map(|index| (index, None)).collect());
+    Ok(order.into_iter().map(|(index, duration)| AnimationFrame{ index, duration: duration.unwrap_or(frame_time) }).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timeline(source: &str, frame_count: usize) -> Vec<(usize, f32)> {
+        parse_metadata(source, frame_count).unwrap().iter().map(|frame| (frame.index, frame.duration)).collect()
+    }
+
+    #[test]
+    fn defaults_to_every_frame_in_order() {
+        assert_eq!(timeline("", 3), vec![(0, DEFAULT_FRAME_TIME), (1, DEFAULT_FRAME_TIME), (2, DEFAULT_FRAME_TIME)]);
+        assert_eq!(timeline("frame_time 0.25", 2), vec![(0, 0.25), (1, 0.25)]);
+    }
+
+    #[test]
+    fn frames_set_the_order_and_their_own_durations() {
+        assert_eq!(timeline("frames 0 1:0.5", 2), vec![(0, DEFAULT_FRAME_TIME), (1, 0.5)]);
+        assert_eq!(timeline("frames 2 0:1 2\nframe_time 0.2", 3), vec![(2, 0.2), (0, 1.0), (2, 0.2)]);
+    }
+
+    #[test]
+    fn comments_and_blank_lines_are_skipped() {
+        assert_eq!(timeline("# slow water\n\n  frame_time 0.5 # seconds\nframes 1 0 # backwards", 2), vec![(1, 0.5), (0, 0.5)]);
+    }
+
+    #[test]
+    fn errors_report_their_line() {
+        assert_eq!(parse_metadata("frame_time 0.1\nframes 0 3", 3).err().map(|(line, _)| line), Some(2));
+        assert_eq!(parse_metadata("frames 0 -1", 3).err().map(|(line, _)| line), Some(1));
+        assert_eq!(parse_metadata("frames 0:0", 3).err().map(|(line, _)| line), Some(1));
+        assert_eq!(parse_metadata("frames 0:x", 3).err().map(|(line, _)| line), Some(1));
+        assert_eq!(parse_metadata("frames", 3).err().map(|(line, _)| line), Some(1));
+        assert_eq!(parse_metadata("frame_time -1", 3).err().map(|(line, _)| line), Some(1));
+        assert_eq!(parse_metadata("\nframe_time 0.1 0.2", 3).err().map(|(line, _)| line), Some(2));
+        assert_eq!(parse_metadata("speed 2", 3).err().map(|(line, _)| line), Some(1));
+    }
+}
@@ -0,0 +1,36 @@
+use crate::render_pass::{FrameContext, RenderPass, RenderStage, RenderState};
+use crate::shader_manager::{ProgramHandle, ShaderManager};
+use crate::shadow_map::SHADOW_MAP_TEXTURE_UNIT;
+
+// Alpha blended blocks (glass, water), back to front after everything opaque and the skybox.
+pub struct TransparentPass {
+    program: ProgramHandle
+}
+
+impl TransparentPass {
+    pub fn new(shader_manager: &mut ShaderManager) -> Self {
+        let program = shader_manager.load_or_fallback("shaders/main_vertex.glsl", "shaders/main_fragment.glsl");
+        TransparentPass{ program }
+    }
+}
+
+impl RenderPass for TransparentPass {
+    fn get_stage(&self) -> RenderStage {
+        RenderStage::Transparent
+    }
+
+    fn get_state(&self) -> RenderState {
+        RenderState::transparent()
+    }
+
+    fn draw(&mut self, context: &mut FrameContext) {
+        let program = context.shader_manager.get(self.program);
+        program.use_program();
+        program.set_texture("custom_texture", 0);
+        program.set_texture("u_shadow_map", SHADOW_MAP_TEXTURE_UNIT as i32);
+        program.set_float("u_alpha_cutoff", 0.0);
+
+        context.texture.activate_texture_array(gl::TEXTURE0, context.texture.get_id(context.chunk_renderer.get_block_textures()));
+        context.chunk_renderer.draw_translucent(context.camera.get_camera_position());
+    }
+}
@@ -0,0 +1,96 @@
+use gl::types::{GLenum, GLint, GLuint};
+
+#[derive(Clone, Copy)]
+pub struct VertexAttribute {
+    pub location: GLuint,
+    pub components: GLint,
+    pub gl_type: GLenum,
+    pub normalized: bool,
+    pub integer: bool, // read as int/uint in the shader instead of being converted to float
+    pub offset: usize,
+    pub divisor: GLuint // 0 advances per vertex, n advances every n instances
+}
+
+// How one interleaved buffer maps onto shader attribute locations.
+pub struct VertexLayout {
+    pub stride: usize,
+    pub attributes: Vec<VertexAttribute>,
+    divisor: GLuint
+}
+
+impl VertexLayout {
+    pub fn new() -> Self {
+        VertexLayout{ stride: 0, attributes: vec![], divisor: 0 }
+    }
+
+    // For per-instance buffers, every attribute added afterwards advances once per instance.
+    pub fn per_instance() -> Self {
+        VertexLayout{ stride: 0, attributes: vec![], divisor: 1 }
+    }
+
+    pub fn float(self, location: GLuint, components: GLint) -> Self {
+        self.push(location, components, gl::FLOAT, false, false)
+    }
+
+    // Unsigned/signed integer data mapped to [0, 1] / [-1, 1] floats, e.g. packed light or AO.
+    pub fn normalized(self, location: GLuint, components: GLint, gl_type: GLenum) -> Self {
+        self.push(location, components, gl_type, true, false)
+    }
+
+    pub fn integer(self, location: GLuint, components: GLint, gl_type: GLenum) -> Self {
+        self.push(location, components, gl_type, false, true)
+    }
+
+    // A mat4 takes four consecutive vec4 locations.
+    pub fn mat4(self, location: GLuint) -> Self {
+        self.float(location, 4).float(location + 1, 4).float(location + 2, 4).float(location + 3, 4)
+    }
+
+    // Explicit attribute, for layouts with padding or non-interleaved offsets. Stride grows to cover it.
+    pub fn attribute(mut self, attribute: VertexAttribute) -> Self {
+        let end = attribute.offset + attribute.components as usize * gl_type_size(attribute.gl_type);
+        self.stride = self.stride.max(end);
+        self.attributes.push(attribute);
+        self
+    }
+
+    fn push(self, location: GLuint, components: GLint, gl_type: GLenum, normalized: bool, integer: bool) -> Self {
+        let attribute = VertexAttribute{ location, components, gl_type, normalized, integer, offset: self.stride, divisor: self.divisor };
+        self.attribute(attribute)
+    }
+}
+
+pub fn gl_type_size(gl_type: GLenum) -> usize {
+    match gl_type {
+        gl::BYTE | gl::UNSIGNED_BYTE => 1,
+        gl::SHORT | gl::UNSIGNED_SHORT | gl::HALF_FLOAT => 2,
+        gl::INT | gl::UNSIGNED_INT | gl::FLOAT => 4,
+        gl::DOUBLE => 8,
+        _ => panic!("Unsupported vertex attribute type: 0x{:04X}", gl_type)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn attributes_are_packed_in_order() {
+        let layout = VertexLayout::new().float(0, 3).normalized(1, 4, gl::UNSIGNED_BYTE).integer(2, 1, gl::UNSIGNED_INT);
+        let offsets: Vec<usize> = layout.attributes.iter().map(|attribute| attribute.offset).collect();
+        assert_eq!(offsets, [0, 12, 16]);
+        assert_eq!(layout.stride, 20);
+        assert!(layout.attributes[1].normalized && !layout.attributes[1].integer);
+        assert!(layout.attributes[2].integer && !layout.attributes[2].normalized);
+        assert!(layout.attributes.iter().all(|attribute| attribute.divisor == 0));
+    }
+
+    #[test]
+    fn per_instance_layouts_advance_once_per_instance() {
+        let layout = VertexLayout::per_instance().mat4(2).integer(6, 1, gl::UNSIGNED_INT);
+        let locations: Vec<GLuint> = layout.attributes.iter().map(|attribute| attribute.location).collect();
+        assert_eq!(locations, [2, 3, 4, 5, 6]);
+        assert_eq!(layout.stride, 17 * 4);
+        assert!(layout.attributes.iter().all(|attribute| attribute.divisor == 1));
+    }
+}
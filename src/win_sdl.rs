@@ -1,7 +1,10 @@
 use sdl2::video::{GLContext, GLProfile, SwapInterval, Window};
 use sdl2::{EventPump, Sdl};
+use std::env;
 use std::os::raw::c_void;
 
+pub const MSAA_SAMPLES: u8 = 4; // of the renderer's scene target, the window itself isn't multisampled
+
 pub struct WinSDL {
     pub sdl: Sdl,
     pub window: Window,
@@ -12,25 +15,45 @@ pub struct WinSDL {
 
 impl WinSDL {
     pub fn new(width: u32, height: u32) -> Result<Self, &'static str> {
-        let sdl = sdl2::init().unwrap();
-        let video_subsystem = sdl.video().unwrap();
+        Self::create(width, height, false)
+    }
+
+    // Hidden window on SDL's offscreen driver (EGL, works with Mesa llvmpipe), no display needed.
+    // Render into a Framebuffer, the default framebuffer of a hidden window is undefined.
+    pub fn new_headless(width: u32, height: u32) -> Result<Self, &'static str> {
+        Self::create(width, height, true)
+    }
+
+    fn create(width: u32, height: u32, headless: bool) -> Result<Self, &'static str> {
+        if headless && env::var_os("SDL_VIDEODRIVER").is_none() {
+            sdl2::hint::set("SDL_VIDEODRIVER", "offscreen");
+        }
+
+        let sdl = sdl2::init().map_err(|_| "Failed to initialize SDL")?;
+        let video_subsystem = sdl.video().map_err(|_| "Failed to initialize SDL video subsystem")?;
 
         let gl_attr = video_subsystem.gl_attr();
         gl_attr.set_context_profile(GLProfile::Core);
         gl_attr.set_context_version(4, 3);
 
-        gl_attr.set_multisample_samples(4); // 4x MSAA
+        let mut window_builder = video_subsystem.window("Abyssal Eclipse", width, height);
+        window_builder.opengl();
+        if headless {
+            window_builder.hidden();
+        } else {
+            window_builder.fullscreen_desktop().borderless();
+        }
+        let window = window_builder.build().map_err(|_| "Failed to create window")?;
 
-        let window = video_subsystem.window("Abyssal Eclipse", width, height).opengl().fullscreen_desktop().borderless().build().unwrap();
-
-        let gl_context = window.gl_create_context().unwrap();
+        let gl_context = window.gl_create_context().map_err(|_| "Failed to create OpenGL context")?;
         let gl = gl::load_with(|s| {
             video_subsystem.gl_get_proc_address(s) as *const c_void
         });
 
-        window.subsystem().gl_set_swap_interval(SwapInterval::VSync).unwrap();
+        let swap_interval = if headless { SwapInterval::Immediate } else { SwapInterval::VSync };
+        window.subsystem().gl_set_swap_interval(swap_interval).map_err(|_| "Failed to set swap interval")?;
 
-        let event_pump = sdl.event_pump().unwrap();
+        let event_pump = sdl.event_pump().map_err(|_| "Failed to get SDL event pump")?;
         Ok(WinSDL{
             sdl,
             window,
@@ -39,4 +62,4 @@ impl WinSDL {
             event_pump,
         })
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,253 @@
+use crate::block::{get_block_type, BlockId, AIR};
+use crate::bounding_box::BoundingBox;
+use nalgebra_glm::{vec3, Vec3};
+use std::collections::HashMap;
+
+pub const CHUNK_SIZE: i32 = 16;
+
+pub type ChunkPosition = (i32, i32, i32);
+
+pub const UNLOADED_SKY_LIGHT: u8 = 15; // outside the world is open sky
+
+// A CHUNK_SIZE^3 section of blocks, stored x fastest, then z, then y. Light levels (0-15) are packed
+// per block, block light in the low and skylight in the high nibble.
+pub struct Chunk {
+    blocks: Vec<BlockId>,
+    light: Vec<u8>
+}
+
+impl Chunk {
+    pub fn new() -> Self {
+        let size = (CHUNK_SIZE * CHUNK_SIZE * CHUNK_SIZE) as usize;
+        Chunk{ blocks: vec![AIR; size], light: vec![0; size] }
+    }
+
+    pub fn get_block(&self, x: i32, y: i32, z: i32) -> BlockId {
+        self.blocks[index(x, y, z)]
+    }
+
+    pub fn set_block(&mut self, x: i32, y: i32, z: i32, block: BlockId) {
+        self.blocks[index(x, y, z)] = block;
+    }
+
+    pub fn get_block_light(&self, x: i32, y: i32, z: i32) -> u8 {
+        self.light[index(x, y, z)] & 0x0F
+    }
+
+    pub fn get_sky_light(&self, x: i32, y: i32, z: i32) -> u8 {
+        self.light[index(x, y, z)] >> 4
+    }
+
+    pub fn set_block_light(&mut self, x: i32, y: i32, z: i32, level: u8) {
+        let light = &mut self.light[index(x, y, z)];
+        *light = (*light & 0xF0) | level;
+    }
+
+    pub fn set_sky_light(&mut self, x: i32, y: i32, z: i32, level: u8) {
+        let light = &mut self.light[index(x, y, z)];
+        *light = (*light & 0x0F) | (level << 4);
+    }
+
+    pub fn clear_light(&mut self) {
+        self.light.fill(0);
+    }
+}
+
+fn index(x: i32, y: i32, z: i32) -> usize {
+    (x + z * CHUNK_SIZE + y * CHUNK_SIZE * CHUNK_SIZE) as usize
+}
+
+pub struct World {
+    chunks: HashMap<ChunkPosition, Chunk>
+}
+
+impl World {
+    pub fn new() -> Self {
+        World{ chunks: HashMap::new() }
+    }
+
+    // Blocks in chunks that were never created are air.
+    pub fn get_block(&self, x: i32, y: i32, z: i32) -> BlockId {
+        let (chunk_position, (local_x, local_y, local_z)) = split(x, y, z);
+        self.chunks.get(&chunk_position).map_or(AIR, |chunk| chunk.get_block(local_x, local_y, local_z))
+    }
+
+    // Returns the chunk the block is in, the caller remeshes it (and its neighbours for border blocks).
+    pub fn set_block(&mut self, x: i32, y: i32, z: i32, block: BlockId) -> ChunkPosition {
+        let (chunk_position, (local_x, local_y, local_z)) = split(x, y, z);
+        self.chunks.entry(chunk_position).or_insert_with(Chunk::new).set_block(local_x, local_y, local_z, block);
+        chunk_position
+    }
+
+    pub fn has_chunk(&self, chunk_position: ChunkPosition) -> bool {
+        self.chunks.contains_key(&chunk_position)
+    }
+
+    pub fn is_loaded(&self, x: i32, y: i32, z: i32) -> bool {
+        self.has_chunk(split(x, y, z).0)
+    }
+
+    pub fn get_block_light(&self, x: i32, y: i32, z: i32) -> u8 {
+        let (chunk_position, (local_x, local_y, local_z)) = split(x, y, z);
+        self.chunks.get(&chunk_position).map_or(0, |chunk| chunk.get_block_light(local_x, local_y, local_z))
+    }
+
+    pub fn get_sky_light(&self, x: i32, y: i32, z: i32) -> u8 {
+        let (chunk_position, (local_x, local_y, local_z)) = split(x, y, z);
+        self.chunks.get(&chunk_position).map_or(UNLOADED_SKY_LIGHT, |chunk| chunk.get_sky_light(local_x, local_y, local_z))
+    }
+
+    // Light outside loaded chunks isn't stored, setting it does nothing.
+    pub fn set_block_light(&mut self, x: i32, y: i32, z: i32, level: u8) {
+        let (chunk_position, (local_x, local_y, local_z)) = split(x, y, z);
+        if let Some(chunk) = self.chunks.get_mut(&chunk_position) {
+            chunk.set_block_light(local_x, local_y, local_z, level);
+        }
+    }
+
+    pub fn set_sky_light(&mut self, x: i32, y: i32, z: i32, level: u8) {
+        let (chunk_position, (local_x, local_y, local_z)) = split(x, y, z);
+        if let Some(chunk) = self.chunks.get_mut(&chunk_position) {
+            chunk.set_sky_light(local_x, local_y, local_z, level);
+        }
+    }
+
+    pub fn clear_light(&mut self) {
+        for chunk in self.chunks.values_mut() {
+            chunk.clear_light();
+        }
+    }
+
+    // Steps through every block the ray passes (Amanatides & Woo) and returns the first non-air
+    // block together with the block the ray was in just before it, where a new block would go.
+    pub fn raycast(&self, origin: Vec3, direction: Vec3, max_distance: f32) -> Option<([i32; 3], [i32; 3])> {
+        let start = origin + vec3(0.5, 0.5, 0.5); // blocks are centered on their integer position
+        let mut position = [start.x.floor() as i32, start.y.floor() as i32, start.z.floor() as i32];
+        let mut step = [0; 3];
+        let mut next_boundary = [f32::INFINITY; 3];
+        let mut boundary_distance = [f32::INFINITY; 3];
+        for axis in 0..3 {
+            if direction[axis] > 0.0 {
+                step[axis] = 1;
+                next_boundary[axis] = (position[axis] as f32 + 1.0 - start[axis]) / direction[axis];
+            } else if direction[axis] < 0.0 {
+                step[axis] = -1;
+                next_boundary[axis] = (start[axis] - position[axis] as f32) / -direction[axis];
+            }
+            if direction[axis] != 0.0 {
+                boundary_distance[axis] = 1.0 / direction[axis].abs();
+            }
+        }
+
+        let mut previous = position;
+        let mut distance = 0.0;
+        while distance <= max_distance {
+            if self.get_block(position[0], position[1], position[2]) != AIR {
+                return Some((position, previous));
+            }
+
+            let axis = (0..3).min_by(|a, b| next_boundary[*a].total_cmp(&next_boundary[*b])).unwrap();
+            previous = position;
+            position[axis] += step[axis];
+            distance = next_boundary[axis];
+            next_boundary[axis] += boundary_distance[axis];
+        }
+        None
+    }
+
+    pub fn get_chunk_positions(&self) -> Vec<ChunkPosition> {
+        self.chunks.keys().copied().collect()
+    }
+
+    pub fn get_bounding_boxes(&self) -> Vec<BoundingBox> {
+        let mut boxes = vec![];
+        for (&(chunk_x, chunk_y, chunk_z), chunk) in &self.chunks {
+            for y in 0..CHUNK_SIZE {
+                for z in 0..CHUNK_SIZE {
+                    for x in 0..CHUNK_SIZE {
+                        if get_block_type(chunk.get_block(x, y, z)).is_some_and(|block_type| block_type.solid) {
+                            let (world_x, world_y, world_z) = (chunk_x * CHUNK_SIZE + x, chunk_y * CHUNK_SIZE + y, chunk_z * CHUNK_SIZE + z);
+                            boxes.push(BoundingBox::new(world_x as f32, world_y as f32, world_z as f32, 0.5, 0.5, 0.5));
+                        }
+                    }
+                }
+            }
+        }
+        boxes
+    }
+}
+
+// World block coordinates to (chunk position, position inside the chunk).
+pub fn split(x: i32, y: i32, z: i32) -> (ChunkPosition, (i32, i32, i32)) {
+    (
+        (x.div_euclid(CHUNK_SIZE), y.div_euclid(CHUNK_SIZE), z.div_euclid(CHUNK_SIZE)),
+        (x.rem_euclid(CHUNK_SIZE), y.rem_euclid(CHUNK_SIZE), z.rem_euclid(CHUNK_SIZE))
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::get_block_id;
+
+    #[test]
+    fn split_rounds_negative_coordinates_down() {
+        assert_eq!(split(0, 0, 0), ((0, 0, 0), (0, 0, 0)));
+        assert_eq!(split(15, 16, 17), ((0, 1, 1), (15, 0, 1)));
+        assert_eq!(split(-1, -16, -17), ((-1, -1, -2), (15, 0, 15)));
+    }
+
+    #[test]
+    fn chunk_index_is_unique() {
+        let mut seen = vec![false; (CHUNK_SIZE * CHUNK_SIZE * CHUNK_SIZE) as usize];
+        for y in 0..CHUNK_SIZE {
+            for z in 0..CHUNK_SIZE {
+                for x in 0..CHUNK_SIZE {
+                    assert!(!seen[index(x, y, z)]);
+                    seen[index(x, y, z)] = true;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn chunk_light_channels_are_independent() {
+        let mut chunk = Chunk::new();
+        chunk.set_block_light(1, 2, 3, 7);
+        chunk.set_sky_light(1, 2, 3, 12);
+        chunk.set_block_light(1, 2, 3, 9);
+        assert_eq!(chunk.get_block_light(1, 2, 3), 9);
+        assert_eq!(chunk.get_sky_light(1, 2, 3), 12);
+        assert_eq!(chunk.get_sky_light(3, 2, 1), 0);
+    }
+
+    #[test]
+    fn world_blocks_land_in_their_chunk() {
+        let dirt = get_block_id("dirt").unwrap();
+        let mut world = World::new();
+        assert_eq!(world.set_block(-1, 20, 5, dirt), (-1, 1, 0));
+        assert_eq!(world.get_block(-1, 20, 5), dirt);
+        assert_eq!(world.get_block(15, 20, 5), AIR);
+        assert_eq!(world.get_block(-17, 20, 5), AIR);
+        assert!(world.is_loaded(-16, 16, 0));
+        assert!(!world.is_loaded(0, 16, 0));
+        assert_eq!(world.get_chunk_positions(), vec![(-1, 1, 0)]);
+    }
+
+    #[test]
+    fn light_outside_loaded_chunks_is_not_stored() {
+        let mut world = World::new();
+        world.set_block_light(0, 0, 0, 10);
+        world.set_sky_light(0, 0, 0, 3);
+        assert_eq!(world.get_block_light(0, 0, 0), 0);
+        assert_eq!(world.get_sky_light(0, 0, 0), UNLOADED_SKY_LIGHT);
+    }
+
+    #[test]
+    fn only_solid_blocks_have_bounding_boxes() {
+        let mut world = World::new();
+        world.set_block(-1, 0, 0, get_block_id("dirt").unwrap());
+        world.set_block(1, 0, 0, get_block_id("water").unwrap());
+        assert!(world.get_bounding_boxes() == vec![BoundingBox::new(-1.0, 0.0, 0.0, 0.5, 0.5, 0.5)]);
+    }
+}
@@ -0,0 +1,137 @@
+use crate::lighting::Lighting;
+use nalgebra_glm::{mix, normalize, vec3, Vec3};
+use std::f32::consts::TAU;
+
+pub const DEFAULT_DAY_LENGTH: f32 = 600.0; // seconds
+pub const DEFAULT_TIME: f32 = 8.0;         // hours
+
+const SUN_TILT: f32 = 0.3; // keeps the sun's path off the x axis so shadows aren't axis aligned
+
+const DAY_SUN_COLOR: Vec3 = Vec3::new(0.7, 0.7, 0.65);
+const MOON_COLOR: Vec3 = Vec3::new(0.12, 0.14, 0.22);
+const DAY_AMBIENT: Vec3 = Vec3::new(0.35, 0.35, 0.4);
+const NIGHT_AMBIENT: Vec3 = Vec3::new(0.05, 0.06, 0.1);
+
+const DAY_ZENITH: Vec3 = Vec3::new(0.25, 0.5, 0.9);
+const DAY_HORIZON: Vec3 = Vec3::new(0.7, 0.8, 0.95);
+const NIGHT_ZENITH: Vec3 = Vec3::new(0.01, 0.01, 0.04);
+const NIGHT_HORIZON: Vec3 = Vec3::new(0.03, 0.04, 0.1);
+const TWILIGHT_HORIZON: Vec3 = Vec3::new(0.95, 0.5, 0.25);
+
+// Time of day in hours: 0 midnight, 6 sunrise, 12 noon, 18 sunset.
+pub struct WorldClock {
+    time: f32,
+    day_length: f32
+}
+
+impl WorldClock {
+    pub fn new(time: f32, day_length: f32) -> Self {
+        WorldClock{ time: time.rem_euclid(24.0), day_length }
+    }
+
+    pub fn update(&mut self, delta_time: f32) {
+        self.set_time(self.time + delta_time / self.day_length * 24.0);
+    }
+
+    pub fn set_time(&mut self, time: f32) {
+        self.time = time.rem_euclid(24.0);
+    }
+
+    pub fn get_time(&self) -> f32 {
+        self.time
+    }
+
+    // Rises in +x, sets in -x.
+    pub fn get_sun_position(&self) -> Vec3 {
+        let angle = (self.time - 6.0) / 24.0 * TAU;
+        normalize(&vec3(angle.cos(), angle.sin(), SUN_TILT))
+    }
+
+    pub fn get_lighting(&self) -> Lighting {
+        let sun_position = self.get_sun_position();
+        let elevation = sun_position.y;
+        let daylight = smoothstep(-0.1, 0.2, elevation);
+        let twilight = (1.0 - elevation.abs() / 0.3).max(0.0); // sun close to the horizon
+
+        // The moon takes over casting shadows once the sun is down. Both fade out towards the horizon,
+        // so the light is off when the direction switches.
+        let (sun_direction, sun_color) = if elevation > 0.0 {
+            (sun_position, DAY_SUN_COLOR * smoothstep(0.0, 0.2, elevation))
+        } else {
+            (-sun_position, MOON_COLOR * smoothstep(0.0, 0.2, -elevation))
+        };
+
+        let horizon = mix(&NIGHT_HORIZON, &DAY_HORIZON, daylight);
+        Lighting{
+            sun_direction,
+            sun_color,
+            ambient_color: mix(&NIGHT_AMBIENT, &DAY_AMBIENT, daylight),
+            sky_zenith_color: mix(&NIGHT_ZENITH, &DAY_ZENITH, daylight),
+            sky_horizon_color: mix(&horizon, &TWILIGHT_HORIZON, twilight * 0.7),
+            sun_position,
+            daylight,
+            star_visibility: 1.0 - smoothstep(-0.2, 0.05, elevation)
+        }
+    }
+}
+
+// Accepts hours ("6.5"), hours and minutes ("18:30") or a name. Times past 24:00 are rejected.
+pub fn parse_time(value: &str) -> Option<f32> {
+    let time = match value {
+        "sunrise" => 6.0,
+        "noon" => 12.0,
+        "sunset" => 18.0,
+        "midnight" => 0.0,
+        _ => match value.split_once(':') {
+            Some((hours, minutes)) => {
+                let minutes = minutes.parse::<u32>().ok().filter(|minutes| *minutes < 60)?;
+                hours.parse::<u32>().ok()? as f32 + minutes as f32 / 60.0
+            },
+            None => value.parse::<f32>().ok().filter(|time| time.is_finite())?
+        }
+    };
+    (0.0..=24.0).contains(&time).then_some(time)
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_hours_minutes_and_names() {
+        assert_eq!(parse_time("6.5"), Some(6.5));
+        assert_eq!(parse_time("18:30"), Some(18.5));
+        assert_eq!(parse_time("0:00"), Some(0.0));
+        assert_eq!(parse_time("24"), Some(24.0));
+        assert_eq!(parse_time("noon"), Some(12.0));
+    }
+
+    #[test]
+    fn rejects_invalid_times() {
+        for value in ["", "NaN", "inf", "-1", "24.5", "25:00", "24:30", "12:60", "12:-5", "-1:30", "12:", ":30", "6.5:30", "dusk"] {
+            assert_eq!(parse_time(value), None, "{}", value);
+        }
+    }
+
+    #[test]
+    fn light_fades_out_before_switching_to_the_moon() {
+        for time in [5.99, 6.0, 6.01, 17.99, 18.0, 18.01] {
+            let lighting = WorldClock::new(time, DEFAULT_DAY_LENGTH).get_lighting();
+            assert!(lighting.sun_color.max() < 0.01, "{}: {:?}", time, lighting.sun_color);
+        }
+    }
+
+    #[test]
+    fn sun_lights_the_day_and_moon_the_night() {
+        let noon = WorldClock::new(12.0, DEFAULT_DAY_LENGTH).get_lighting();
+        assert!(noon.sun_direction.y > 0.9 && noon.sun_color == DAY_SUN_COLOR);
+
+        let midnight = WorldClock::new(0.0, DEFAULT_DAY_LENGTH).get_lighting();
+        assert!(midnight.sun_direction.y > 0.9 && midnight.sun_color == MOON_COLOR);
+    }
+}
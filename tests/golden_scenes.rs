@@ -0,0 +1,14 @@
+use std::process::Command;
+
+// Renders the golden scenes through the real binary and compares them with tests/golden.
+// Needs an OpenGL 4.3 context, CI runs it on Mesa's software renderer:
+//   cargo test -- --ignored
+#[test]
+#[ignore]
+fn golden_scenes_match_their_references() {
+    let status = Command::new(env!("CARGO_BIN_EXE_abyssal-eclipse"))
+        .arg("--golden")
+        .status()
+        .expect("Couldn't start the game");
+    assert!(status.success(), "Golden scenes differ, see target/golden for the rendered images and diffs");
+}